@@ -22,6 +22,27 @@ pub struct AssemblyPower {
     pub max_power: f32,
     pub powering_entities: Vec<Entity>,
     pub power_cost: f32,
+    // Other power types the machine can run on and how much of it is usable
+    pub accepted_power: Vec<(Power, f32)>,
+}
+impl AssemblyPower {
+    // None if the machine can't run on this type of power at all
+    pub fn get_efficiency(&self, power: Power) -> Option<f32> {
+        if power.is_same_type(self.current_power) {
+            return Some(1.0);
+        }
+        self.accepted_power.iter()
+            .find(|(accepted, _)| accepted.is_same_type(power))
+            .map(|(_, efficiency)| *efficiency)
+    }
+
+    pub fn get_accepted_names(&self) -> String {
+        std::iter::once(self.current_power)
+            .chain(self.accepted_power.iter().map(|(accepted, _)| *accepted))
+            .map(|power| power.get_name())
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
 }
 
 pub fn input_toggle_assembly_mode(
@@ -66,15 +87,15 @@ pub fn place_assembly(
     mut money: ResMut<PlayerMoney>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    asset_server: Res<AssetServer>,
     tilemap_q: Query<(
         &TilemapSize,
         &TilemapGridSize,
         &TilemapType,
         &Transform
     )>,
-    q_collision_tiles: Query<&TilePos, With<TileMapCollision>>,
-    sprites: Res<SpriteStorage>
+    collision_grid: Res<CollisionGrid>,
+    sprites: Res<SpriteStorage>,
+    definitions: Res<AssemblyDefinitions>
 ) {
     if input.just_pressed(MouseButton::Left) {
         let price = assembly_prices.prices.get(&selected_assembly.selected);
@@ -90,22 +111,35 @@ pub fn place_assembly(
         let (tilemap_size, grid_size, map_type, map_transform) = tilemap_q.single();
 
         let Some(tile_pos) = get_mouse_tile(window, camera, camera_transform, tilemap_size, grid_size, map_type, map_transform) else { return };
-        let size = selected_assembly.selected.get_tile_size(&sprites).0;
+        let size = selected_assembly.selected.get_tile_size(&sprites, &definitions).0;
         let pos = get_corner_tile_pos(get_tile_world_pos(&tile_pos, map_transform, grid_size, map_type), size);
-        if q_collision_tiles.iter().any(|p| *p == tile_pos) {
+        if collision_grid.is_solid(&tile_pos) {
             println!("Can't place assembly here");
             return;
         }
-        let mut output_bundle = ContainerOutputSelectorBundle::new(asset_server.clone());
+        spawn_assembly(&mut commands, &sprites, &definitions, selected_assembly.selected, pos);
+    }
+}
 
-        output_bundle.sprite.transform.translation = Vec3::new(-(size.x as f32) * TILE_SIZE.x, 0.0, 1.0);
-        output_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI / 2.0);
-        let output_entity = commands.spawn(output_bundle).id();
+pub fn spawn_assembly(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    definitions: &AssemblyDefinitions,
+    assembly_type: AssemblyType,
+    pos: Vec2
+) -> Entity {
+    let size = assembly_type.get_tile_size(sprites, definitions).0;
+    let mut output_bundle = ContainerOutputSelectorBundle::new(sprites);
 
-        let mut input_bundle = ContainerInputSelectorBundle::new(asset_server.clone());
-        input_bundle.sprite.transform.translation = Vec3::new((size.x as f32) * TILE_SIZE.x, 0.0, 1.0);
-        input_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI / 2.0);
-        let input_entity: Entity = commands.spawn(input_bundle).id();
-        selected_assembly.selected.spawn_bundle(&mut commands, &sprites, pos).push_children(&[input_entity, output_entity]);
-    }
+    output_bundle.sprite.transform.translation = Vec3::new(-(size.x as f32) * TILE_SIZE.x, 0.0, 1.0);
+    output_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI / 2.0);
+    let output_entity = commands.spawn(output_bundle).id();
+
+    let mut input_bundle = ContainerInputSelectorBundle::new(sprites);
+    input_bundle.sprite.transform.translation = Vec3::new((size.x as f32) * TILE_SIZE.x, 0.0, 1.0);
+    input_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI / 2.0);
+    let input_entity: Entity = commands.spawn(input_bundle).id();
+    let mut assembly_commands = assembly_type.spawn_bundle(commands, sprites, definitions, pos);
+    assembly_commands.push_children(&[input_entity, output_entity]);
+    assembly_commands.id()
 }
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use bevy::{asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadedFolder}, utils::{BoxedFuture, HashSet}};
+
+use crate::*;
+
+use super::assembly_types::assembly_templates::*;
+
+pub const ASSEMBLY_DEFINITIONS_FOLDER: &str = "assemblies";
+
+// Machines described in assets/assemblies/*.assembly.ron
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct AssemblyDefinition {
+    // Stored in AssemblyType::Custom, so saves and templates break if it changes
+    pub id: u16,
+    pub name: String,
+    pub tile_size: (i32, i32),
+    // The first recipe is selected when the machine is placed
+    pub recipes: Vec<Recipe>,
+    pub production_time: f32,
+    // Type of power the machine runs on and how much it needs
+    pub power: Power,
+    pub max_power: f32,
+    // Other power types it runs on, with the fraction of that power that is usable
+    #[serde(default)]
+    pub accepted_power: Vec<(Power, f32)>,
+    pub input_capacity: usize,
+    pub output_capacity: usize,
+    pub price: f32,
+    pub icon: String,
+    #[serde(default)]
+    pub generator: Option<PowerGenerator>,
+    #[serde(default)]
+    pub hover_icon: Option<String>,
+    #[serde(default)]
+    pub selected_icon: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct AssemblyIcons {
+    pub icon: Handle<Image>,
+    pub hover: Handle<Image>,
+    pub selected: Handle<Image>,
+}
+
+#[derive(Resource, Default)]
+pub struct AssemblyDefinitions {
+    pub folder: Handle<LoadedFolder>,
+    // Sorted by name for the build menu, icons line up with definitions
+    pub definitions: Vec<AssemblyDefinition>,
+    pub icons: Vec<AssemblyIcons>,
+}
+impl AssemblyDefinitions {
+    pub fn get(&self, id: u16) -> Option<&AssemblyDefinition> {
+        self.definitions.iter().find(|definition| definition.id == id)
+    }
+
+    pub fn get_icons(&self, id: u16) -> AssemblyIcons {
+        self.definitions.iter()
+            .position(|definition| definition.id == id)
+            .and_then(|i| self.icons.get(i))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+pub enum AssemblyDefinitionLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+impl fmt::Display for AssemblyDefinitionLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssemblyDefinitionLoaderError::Io(e) => write!(f, "Could not read assembly definition: {}", e),
+            AssemblyDefinitionLoaderError::Ron(e) => write!(f, "Could not parse assembly definition: {}", e),
+        }
+    }
+}
+impl std::error::Error for AssemblyDefinitionLoaderError {}
+impl From<std::io::Error> for AssemblyDefinitionLoaderError {
+    fn from(e: std::io::Error) -> Self {
+        AssemblyDefinitionLoaderError::Io(e)
+    }
+}
+impl From<ron::error::SpannedError> for AssemblyDefinitionLoaderError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        AssemblyDefinitionLoaderError::Ron(e)
+    }
+}
+
+#[derive(Default)]
+pub struct AssemblyDefinitionLoader;
+impl AssetLoader for AssemblyDefinitionLoader {
+    type Asset = AssemblyDefinition;
+    type Settings = ();
+    type Error = AssemblyDefinitionLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AssemblyDefinition, AssemblyDefinitionLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<AssemblyDefinition>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["assembly.ron"]
+    }
+}
+
+pub fn load_assembly_definitions(
+    asset_server: Res<AssetServer>,
+    mut definitions: ResMut<AssemblyDefinitions>,
+) {
+    definitions.folder = asset_server.load_folder(ASSEMBLY_DEFINITIONS_FOLDER);
+}
+
+pub fn register_assembly_definitions(
+    mut ev_folder: EventReader<AssetEvent<LoadedFolder>>,
+    folders: Res<Assets<LoadedFolder>>,
+    definition_assets: Res<Assets<AssemblyDefinition>>,
+    asset_server: Res<AssetServer>,
+    // Headless runs have no image assets
+    images: Option<Res<Assets<Image>>>,
+    mut definitions: ResMut<AssemblyDefinitions>,
+    mut assembly_prices: ResMut<AssemblyPrices>,
+) {
+    for ev in ev_folder.read() {
+        let AssetEvent::LoadedWithDependencies { id } = ev else { continue };
+        if *id != definitions.folder.id() { continue }
+        let Some(folder) = folders.get(*id) else { continue };
+
+        let mut loaded = folder.handles.iter()
+            .filter(|handle| handle.type_id() == std::any::TypeId::of::<AssemblyDefinition>())
+            .map(|handle| handle.clone().typed::<AssemblyDefinition>())
+            .filter_map(|handle| definition_assets.get(&handle).cloned())
+            .collect::<Vec<_>>();
+        loaded.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut ids = HashSet::new();
+        loaded.retain(|definition| {
+            if !ids.insert(definition.id) {
+                println!("Skipping assembly {}, id {} is already used", definition.name, definition.id);
+                return false;
+            }
+            true
+        });
+
+        definitions.icons = loaded.iter().map(|definition| {
+            if images.is_none() {
+                return AssemblyIcons::default();
+            }
+            let icon: Handle<Image> = asset_server.load(&definition.icon);
+            AssemblyIcons {
+                hover: definition.hover_icon.as_ref().map_or(icon.clone(), |path| asset_server.load(path)),
+                selected: definition.selected_icon.as_ref().map_or(icon.clone(), |path| asset_server.load(path)),
+                icon,
+            }
+        }).collect();
+        for definition in loaded.iter() {
+            println!("Loaded assembly {}", definition.name);
+            assembly_prices.prices.insert(AssemblyType::Custom(definition.id), definition.price);
+        }
+        definitions.definitions = loaded;
+    }
+}
+
+#[derive(Event)]
+pub struct ShowDefinedAssemblyGhost {
+    pub id: u16
+}
+
+pub fn show_defined_assembly_ghost(
+    mut commands: Commands,
+    mut ev_show_ghost: EventReader<ShowDefinedAssemblyGhost>,
+    definitions: Res<AssemblyDefinitions>,
+) {
+    for ev in ev_show_ghost.read() {
+        let Some(definition) = definitions.get(ev.id) else { continue };
+        let mut bundle = DefinedAssemblyBundle::from_definition(definition, &definitions.get_icons(ev.id));
+        bundle.sprite.sprite.color.set_a(0.5);
+        // Position is set by hover_ghost_tracking
+        commands.spawn((bundle.sprite, bundle.tile_size, HoverGhost));
+    }
+}
@@ -2,11 +2,126 @@ use bevy::sprite::Anchor;
 
 use crate::*;
 
-#[derive(Component, Debug)]
-pub struct AssemblyInput(pub Option<Item>);
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Recipe {
+    pub inputs: Vec<(Item, usize)>,
+    pub outputs: Vec<(Item, usize)>,
+}
+impl Recipe {
+    pub fn new(inputs: Vec<(Item, usize)>, outputs: Vec<(Item, usize)>) -> Self {
+        Recipe { inputs, outputs }
+    }
 
-#[derive(Component, Debug)]
-pub struct AssemblyOutput(pub Option<Item>);
+    // Input containers can only filter on a single item type
+    pub fn get_input_type(&self) -> Option<Item> {
+        let (first, _) = self.inputs.first()?;
+        if self.inputs.iter().all(|(item, _)| item == first) {
+            return Some(*first);
+        }
+        None
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.outputs.iter().map(|(_, amount)| amount).sum()
+    }
+
+    // Returns the input entities used for one craft, or None if any ingredient is missing
+    pub fn find_ingredients(&self, container: &ItemContainer, q_items: &Query<&Item>) -> Option<Vec<Entity>> {
+        let mut ingredients = Vec::new();
+        for (input, amount) in self.inputs.iter() {
+            let found = container.items.iter()
+                .filter_map(|item| *item)
+                .filter(|entity| !ingredients.contains(entity))
+                .filter(|entity| q_items.get(*entity).is_ok_and(|item| item == input))
+                .take(*amount)
+                .collect::<Vec<_>>();
+            if found.len() < *amount {
+                return None;
+            }
+            ingredients.extend(found);
+        }
+        Some(ingredients)
+    }
+
+    pub fn get_description(&self) -> String {
+        let describe = |items: &Vec<(Item, usize)>| items.iter()
+            .map(|(item, amount)| format!("{} {}", amount, item.get_name()))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        format!("{} -> {}", describe(&self.inputs), describe(&self.outputs))
+    }
+}
+
+// Recipes an assembly can switch between, the active one is its Recipe component
+#[derive(Component, Clone, Debug)]
+pub struct AssemblyRecipes {
+    pub recipes: Vec<Recipe>,
+    pub selected: usize,
+}
+impl AssemblyRecipes {
+    pub fn new(recipes: Vec<Recipe>) -> Self {
+        AssemblyRecipes { recipes, selected: 0 }
+    }
+
+    pub fn get_selected(&self) -> Recipe {
+        self.recipes.get(self.selected).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Event)]
+pub struct SelectRecipeEvent {
+    pub assembly: Entity,
+    pub recipe: usize,
+}
+
+pub fn select_assembly_recipe(
+    mut ev_select_recipe: EventReader<SelectRecipeEvent>,
+    mut q_assembly: Query<(&mut ItemIOContainer, &mut AssemblyRecipes, &mut Recipe, &mut AssemblyTimer)>,
+    mut q_items: Query<(&Item, &mut Transform)>,
+) {
+    for ev in ev_select_recipe.read() {
+        let Ok((mut assembly_items, mut recipes, mut recipe, mut timer)) = q_assembly.get_mut(ev.assembly) else { continue };
+        if recipes.selected == ev.recipe { continue }
+        let Some(new_recipe) = recipes.recipes.get(ev.recipe).cloned() else { continue };
+
+        // Items the new recipe can't use are flushed to the output so they can be picked up again
+        let incompatible = assembly_items.input.items.iter()
+            .filter_map(|item| *item)
+            .filter(|entity| q_items.get(*entity).is_ok_and(|(item, _)| {
+                !new_recipe.inputs.iter().any(|(input, _)| input == item)
+            }))
+            .collect::<Vec<_>>();
+        if assembly_items.output.items.len() + incompatible.len() > assembly_items.output.max_items {
+            println!("Not enough output space to switch recipe");
+            continue;
+        }
+
+        for entity in incompatible {
+            let Ok((item, mut transform)) = q_items.get_mut(entity) else { continue };
+            let item = *item;
+            *transform = assembly_items.output.get_transform();
+            if let Err(err) = assembly_items.input.remove_item(Some(entity)) {
+                println!("Error flushing input item: {:?}", err);
+                continue;
+            }
+            if let Err(err) = assembly_items.output.add_item((Some(entity), Some(item))) {
+                println!("Error flushing input item: {:?}", err);
+            }
+        }
+        for (i, entity) in assembly_items.input.items.iter().enumerate() {
+            let Some(entity) = entity else { continue };
+            if let Ok((_, mut transform)) = q_items.get_mut(*entity) {
+                *transform = assembly_items.input.get_transform_at_index(i);
+            }
+        }
+
+        assembly_items.input.item_type = new_recipe.get_input_type();
+        timer.timer.reset();
+        timer.item = None;
+        *recipe = new_recipe;
+        recipes.selected = ev.recipe;
+    }
+}
 
 #[derive(Component, Debug)]
 pub struct AssemblyTimer {
@@ -123,7 +238,7 @@ pub fn update_assembly_progress_bars(
 
 pub fn produce_goods(
     mut commands: Commands,
-    mut q_assembly: Query<(Entity, &mut ItemIOContainer, &AssemblyInput, &AssemblyOutput)>,
+    mut q_assembly: Query<(Entity, &mut ItemIOContainer, &Recipe)>,
     mut q_assembly_timer: Query<&mut AssemblyTimer>,
     mut q_assembly_power: Query<&mut AssemblyPower>,
     mut q_jobs: Query<&mut Job>,
@@ -134,33 +249,31 @@ pub fn produce_goods(
     for (
         assembly_entity,
         mut assembly_items,
-        assembly_input,
-        assembly_output
+        recipe
     ) in q_assembly.iter_mut() {
-        let mut timer_item = q_assembly_timer.get_mut(assembly_entity).as_ref().map(|t| t.item).unwrap_or(None);
-        if !assembly_items.input.items.contains(&timer_item) {
-            timer_item = None;
-        }
+        // Generators have no recipe to work on
+        if recipe.inputs.is_empty() { continue }
+        let ingredients = recipe.find_ingredients(&assembly_items.input, &q_items);
 
         if let Ok(mut timer) = q_assembly_timer.get_mut(assembly_entity) {
-            if timer_item.is_none() {
-                timer.timer.reset();
-                let next_item = assembly_items.input.items.get(0);
-                if let Some(next_item) = next_item {
-                    timer.item = *next_item;
-                    timer_item = *next_item;
+            let timer_item = timer.item;
+            match &ingredients {
+                Some(ingredients) => {
+                    // Restart if the item being worked on was taken out
+                    if timer_item.is_none() || !ingredients.contains(&timer_item.unwrap()) {
+                        timer.timer.reset();
+                        timer.item = ingredients.first().copied();
+                    }
+                },
+                None => {
+                    timer.timer.reset();
+                    timer.item = None;
                 }
             }
         }
 
-        let (Some(Some(mut input_entity)), Some(assembly_input)) = (assembly_items.input.items.last_mut(), &assembly_input.0) else { continue; };
-        let Ok(item) = q_items.get(input_entity) else { continue; };
-        if assembly_input != item {
-            continue;
-        }
-
-        if assembly_items.input.items.is_empty() ||
-        assembly_items.output.max_items == assembly_items.output.items.len() {
+        let Some(ingredients) = ingredients else { continue; };
+        if assembly_items.output.items.len() + recipe.output_count() > assembly_items.output.max_items {
             continue;
         }
         let mut power_mult = 1.0;
@@ -174,60 +287,51 @@ pub fn produce_goods(
         }
 
         if let Ok(mut timer) = q_assembly_timer.get_mut(assembly_entity) {
-            if timer_item.is_none() || !timer.timer.tick(time.delta().mul_f32(power_mult)).just_finished() {
+            if !timer.timer.tick(time.delta().mul_f32(power_mult)).just_finished() {
                 continue;
             }
+            timer.item = None;
         }
 
-        let mut finish_production = || {
-            if let Ok(mut power) = q_assembly_power.get_mut(assembly_entity) {
-                power.current_power = match power.current_power {
-                    Power::Electrical(_) => {
-                        Power::Electrical(0.0)
-                    },
-                    Power::Thermal(_) => {
-                        Power::Thermal(0.0)
-                    },
-                    Power::Mechanical(_) => {
-                        Power::Mechanical(0.0)
-                    }
-                };
-                for entity in power.powering_entities.drain(..) {
-                    let Ok(mut job) = q_jobs.get_mut(entity) else { continue };
-                    let Some(current_job_i) = job.current_job else { continue };
-                    let Some(current_job) = job.path.get_mut(current_job_i) else { continue };
-                    
-                    current_job.job_status = JobStatus::Completed;
-                }
+        for input_entity in ingredients.iter() {
+            if assembly_items.input.remove_item(Some(*input_entity)).is_ok() {
+                commands.entity(assembly_entity).remove_children(&[*input_entity]);
+                commands.entity(*input_entity).insert(DespawnLater);
             }
-        };
-
-        if let Some(assembly_output) = &assembly_output.0 {
-            let mut output_entity_commands: bevy::ecs::system::EntityCommands<'_, '_, '_> =
-                assembly_output.spawn_bundle_with_transform(&mut commands, assembly_items.output.get_transform(), sprites.as_ref());
-
-            let output_entity = output_entity_commands.id();
-            if let Ok(_) = assembly_items.output.add_item((Some(output_entity), Some(*assembly_output))) {
-                if let Ok(_) = assembly_items.input.remove_item(Some(input_entity)) {
-                    commands.entity(assembly_entity).remove_children(&[input_entity]);
-                    commands.entity(input_entity).insert(DespawnLater);
-                    commands.entity(assembly_entity).push_children(&[output_entity]);
+        }
 
-                    finish_production();
-                } else {
+        for (output, amount) in recipe.outputs.iter() {
+            for _ in 0..*amount {
+                let mut output_entity_commands =
+                    output.spawn_bundle_with_transform(&mut commands, assembly_items.output.get_transform(), sprites.as_ref());
+                let output_entity = output_entity_commands.id();
+                if let Err(err) = assembly_items.output.add_item((Some(output_entity), Some(*output))) {
+                    println!("Error adding output item: {:?}", err);
                     output_entity_commands.despawn();
-                    if let Err(err) = assembly_items.output.remove_item(Some(output_entity)) {}
+                    continue;
                 }
-            } else {
-                output_entity_commands.despawn();
-                if let Err(err) = assembly_items.output.remove_item(Some(output_entity)) {}
+                commands.entity(assembly_entity).push_children(&[output_entity]);
             }
-        } else {
-            if let Ok(_) = assembly_items.input.remove_item(Some(input_entity)) {
-                commands.entity(assembly_entity).remove_children(&[input_entity]);
-                commands.entity(input_entity).insert(DespawnLater);
+        }
 
-                finish_production();
+        if let Ok(mut power) = q_assembly_power.get_mut(assembly_entity) {
+            power.current_power = match power.current_power {
+                Power::Electrical(_) => {
+                    Power::Electrical(0.0)
+                },
+                Power::Thermal(_) => {
+                    Power::Thermal(0.0)
+                },
+                Power::Mechanical(_) => {
+                    Power::Mechanical(0.0)
+                }
+            };
+            for entity in power.powering_entities.drain(..) {
+                let Ok(mut job) = q_jobs.get_mut(entity) else { continue };
+                let Some(current_job_i) = job.current_job else { continue };
+                let Some(current_job) = job.path.get_mut(current_job_i) else { continue };
+                
+                current_job.job_status = JobStatus::Completed;
             }
         }
     }
@@ -44,17 +44,27 @@ macro_rules! make_assembly_bundle {
 
 make_assembly_bundle!(WoodChipperBundle,
     power: AssemblyPower,
-    input: AssemblyInput,
-    output: AssemblyOutput,
+    recipe: Recipe,
+    recipes: AssemblyRecipes,
     timer: AssemblyTimer
 );
 impl DefaultWithSprites for WoodChipperBundle {
     fn default_with_sprites(sprites: &SpriteStorage) -> WoodChipperBundle {
+        let recipes = AssemblyRecipes::new(vec![
+            Recipe::new(
+                vec![(Item::Resource(ResourceItem::Wood), 1)],
+                vec![(Item::Resource(ResourceItem::WoodChips), 1)]
+            ),
+            Recipe::new(
+                vec![(Item::Resource(ResourceItem::Lumber), 1)],
+                vec![(Item::Resource(ResourceItem::WoodChips), 2)]
+            ),
+        ]);
         WoodChipperBundle {
             assembly_type: AssemblyType::WoodChipper,
             assembly: Assembly,
-            input: AssemblyInput(Some(Item::Resource(ResourceItem::Wood))),
-            output: AssemblyOutput(Some(Item::Resource(ResourceItem::WoodChips))),
+            recipe: recipes.get_selected(),
+            recipes,
             timer: AssemblyTimer {
                 timer: Timer::from_seconds(15.0, TimerMode::Repeating),
                 item: None
@@ -63,7 +73,8 @@ impl DefaultWithSprites for WoodChipperBundle {
                 current_power: Power::Mechanical(0.0),
                 max_power: 45.0,
                 power_cost: 16.0,
-                powering_entities: Vec::new()
+                powering_entities: Vec::new(),
+                accepted_power: Vec::new()
             },
             assembly_items: ItemIOContainer {
                 input: ItemContainer {
@@ -99,17 +110,23 @@ impl DefaultWithSprites for WoodChipperBundle {
 
 make_assembly_bundle!(PulpMachineBundle, 
     power: AssemblyPower,
-    input: AssemblyInput,
-    output: AssemblyOutput,
+    recipe: Recipe,
+    recipes: AssemblyRecipes,
     timer: AssemblyTimer
 );
 impl DefaultWithSprites for PulpMachineBundle {
     fn default_with_sprites(sprites: &SpriteStorage) -> PulpMachineBundle {
+        let recipes = AssemblyRecipes::new(vec![
+            Recipe::new(
+                vec![(Item::Resource(ResourceItem::WoodChips), 1)],
+                vec![(Item::Material(MaterialItem::WoodPulp), 1)]
+            ),
+        ]);
         PulpMachineBundle {
             assembly_type: AssemblyType::PulpMachine,
             assembly: Assembly,
-            input: AssemblyInput(Some(Item::Resource(ResourceItem::WoodChips))),
-            output: AssemblyOutput(Some(Item::Material(MaterialItem::WoodPulp))),
+            recipe: recipes.get_selected(),
+            recipes,
             timer: AssemblyTimer {
                 timer: Timer::from_seconds(9.0, TimerMode::Repeating),
                 item: None
@@ -118,7 +135,8 @@ impl DefaultWithSprites for PulpMachineBundle {
                 current_power: Power::Mechanical(0.0),
                 max_power: 100.0,
                 power_cost: 25.0,
-                powering_entities: Vec::new()
+                powering_entities: Vec::new(),
+                accepted_power: Vec::new()
             },
             assembly_items: ItemIOContainer {
                 input: ItemContainer {
@@ -153,23 +171,30 @@ impl DefaultWithSprites for PulpMachineBundle {
 }
 
 make_assembly_bundle!(PaperMachineBundle,
-    input: AssemblyInput,
-    output: AssemblyOutput,
+    recipe: Recipe,
+    recipes: AssemblyRecipes,
     power: AssemblyPower,
     timer: AssemblyTimer
 );
 impl DefaultWithSprites for PaperMachineBundle {
     fn default_with_sprites(sprites: &SpriteStorage) -> Self {
+        let recipes = AssemblyRecipes::new(vec![
+            Recipe::new(
+                vec![(Item::Material(MaterialItem::WoodPulp), 1)],
+                vec![(Item::Good(GoodItem::Paper), 1)]
+            ),
+        ]);
         PaperMachineBundle {
             assembly_type: AssemblyType::PaperMachine,
             assembly: Assembly,
-            input: AssemblyInput(Some(Item::Material(MaterialItem::WoodPulp))),
-            output: AssemblyOutput(Some(Item::Good(GoodItem::Paper))),
+            recipe: recipes.get_selected(),
+            recipes,
             power: AssemblyPower {
                 current_power: Power::Mechanical(0.0),
                 max_power: 150.0,
                 power_cost: 45.0,
-                powering_entities: Vec::new()
+                powering_entities: Vec::new(),
+                accepted_power: vec![(Power::Electrical(0.0), 0.8)]
             },
             timer: AssemblyTimer {
                 timer: Timer::from_seconds(25.0, TimerMode::Repeating),
@@ -208,18 +233,28 @@ impl DefaultWithSprites for PaperMachineBundle {
 }
 
 make_assembly_bundle!(SawMillBundle,
-    input: AssemblyInput,
-    output: AssemblyOutput,
+    recipe: Recipe,
+    recipes: AssemblyRecipes,
     timer: AssemblyTimer,
     power: AssemblyPower
 );
 impl DefaultWithSprites for SawMillBundle {
     fn default_with_sprites(sprites: &SpriteStorage) -> Self {
+        let recipes = AssemblyRecipes::new(vec![
+            Recipe::new(
+                vec![(Item::Resource(ResourceItem::Wood), 1)],
+                vec![(Item::Resource(ResourceItem::Lumber), 1), (Item::Resource(ResourceItem::WoodChips), 1)]
+            ),
+            Recipe::new(
+                vec![(Item::Resource(ResourceItem::Wood), 1)],
+                vec![(Item::Resource(ResourceItem::WoodChips), 3)]
+            ),
+        ]);
         SawMillBundle {
             assembly_type: AssemblyType::SawMill,
             assembly: Assembly,
-            input: AssemblyInput(Some(Item::Resource(ResourceItem::Wood))),
-            output: AssemblyOutput(Some(Item::Resource(ResourceItem::Lumber))),
+            recipe: recipes.get_selected(),
+            recipes,
             timer: AssemblyTimer {
                 timer: Timer::from_seconds(5.0, TimerMode::Repeating),
                 item: None
@@ -228,7 +263,8 @@ impl DefaultWithSprites for SawMillBundle {
                 current_power: Power::Mechanical(0.0),
                 max_power: 45.0,
                 power_cost: 10.0,
-                powering_entities: Vec::new()
+                powering_entities: Vec::new(),
+                accepted_power: vec![(Power::Electrical(0.0), 0.8)]
             },
             assembly_items: ItemIOContainer {
                 input: ItemContainer {
@@ -261,3 +297,66 @@ impl DefaultWithSprites for SawMillBundle {
         }
     }
 }
+
+make_assembly_bundle!(DefinedAssemblyBundle,
+    power: AssemblyPower,
+    recipe: Recipe,
+    recipes: AssemblyRecipes,
+    timer: AssemblyTimer
+);
+impl DefinedAssemblyBundle {
+    pub fn from_definition(definition: &AssemblyDefinition, icons: &AssemblyIcons) -> Self {
+        let size = IVec2::new(definition.tile_size.0, definition.tile_size.1);
+        let half_size = Vec2::new(size.x as f32 * TILE_SIZE.x, size.y as f32 * TILE_SIZE.y) / 2.0;
+        let recipes = AssemblyRecipes::new(definition.recipes.clone());
+        let recipe = recipes.get_selected();
+        // Generators only accept their fuel
+        let input_type = recipe.get_input_type()
+            .or(definition.generator.as_ref().and_then(|generator| generator.fuel));
+        DefinedAssemblyBundle {
+            assembly_type: AssemblyType::Custom(definition.id),
+            assembly: Assembly,
+            recipe,
+            recipes,
+            timer: AssemblyTimer {
+                timer: Timer::from_seconds(definition.production_time, TimerMode::Repeating),
+                item: None
+            },
+            power: AssemblyPower {
+                current_power: definition.power * 0.0,
+                max_power: definition.max_power,
+                power_cost: match definition.power {
+                    Power::Mechanical(cost) | Power::Thermal(cost) | Power::Electrical(cost) => cost
+                },
+                powering_entities: Vec::new(),
+                accepted_power: definition.accepted_power.clone()
+            },
+            assembly_items: ItemIOContainer {
+                input: ItemContainer {
+                    items: Vec::new(),
+                    item_type: input_type,
+                    max_items: definition.input_capacity,
+                    start_transform: Transform::from_xyz(-half_size.x + 6.0, half_size.y - 6.0, 2.0),
+                    width: size.x,
+                },
+                output: ItemContainer {
+                    items: Vec::new(),
+                    item_type: None,
+                    max_items: definition.output_capacity,
+                    start_transform: Transform::from_xyz(-half_size.x + 6.0, -half_size.y + 6.0, 2.0),
+                    width: size.x,
+                }
+            },
+            solid: SolidEntity,
+            tile_size: EntityTileSize(size),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(half_size * 2.0),
+                    ..default()
+                },
+                texture: icons.icon.clone(),
+                ..AssemblyBundle::default().sprite
+            }
+        }
+    }
+}
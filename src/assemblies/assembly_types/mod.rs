@@ -47,15 +47,18 @@ pub trait AssemblySpawn<'a, 'w, 's> {
         &self,
         commands: &'a mut Commands<'w, 's>,
         sprites: &SpriteStorage,
+        definitions: &AssemblyDefinitions,
         position: Vec2,
     ) -> EntityCommands<'w, 's, 'a>;
 }
 
 macro_rules! make_assembly_types {
     ($(($assembly_name:ident, $bundle:ident)),*) => {
-        #[derive(Component, Debug, Resource, Reflect, Hash, PartialEq, Eq, Clone, Copy)]
+        #[derive(Component, Debug, Resource, Reflect, Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
         pub enum AssemblyType {
-            $($assembly_name),*
+            $($assembly_name,)*
+            // Id of an AssemblyDefinition loaded from .assembly.ron files
+            Custom(u16)
         }
         impl Default for AssemblyType {
             fn default() -> Self {
@@ -68,6 +71,7 @@ macro_rules! make_assembly_types {
                 &self,
                 commands: &'a mut Commands<'w, 's>,
                 sprites: &SpriteStorage,
+                definitions: &AssemblyDefinitions,
                 position: Vec2
             ) -> EntityCommands<'w, 's, 'a> {
                 match self {
@@ -76,16 +80,57 @@ macro_rules! make_assembly_types {
                         bundle.sprite.transform.translation = Vec3::new(position.x, position.y, 1.0);
                         commands.spawn(bundle)
                     }),*
+                    AssemblyType::Custom(id) => {
+                        let Some(definition) = definitions.get(*id) else {
+                            println!("Missing assembly definition {}", id);
+                            return commands.spawn_empty();
+                        };
+                        let mut bundle = DefinedAssemblyBundle::from_definition(definition, &definitions.get_icons(*id));
+                        bundle.sprite.transform.translation = Vec3::new(position.x, position.y, 1.0);
+                        let mut assembly_commands = commands.spawn(bundle);
+                        if let Some(generator) = &definition.generator {
+                            assembly_commands.insert(generator.clone());
+                        }
+                        assembly_commands
+                    }
                 }
             }
         }
 
         impl AssemblyType {
-            pub fn get_tile_size(self, sprites: &SpriteStorage) -> EntityTileSize {
+            pub fn get_tile_size(self, sprites: &SpriteStorage, definitions: &AssemblyDefinitions) -> EntityTileSize {
                 match self {
                     $(AssemblyType::$assembly_name => {
                         $bundle::default_with_sprites(sprites).tile_size
                     })*,
+                    AssemblyType::Custom(id) => {
+                        let size = definitions.get(id).map_or((1, 1), |d| d.tile_size);
+                        EntityTileSize(IVec2::new(size.0, size.1))
+                    }
+                }
+            }
+
+            pub fn get_io_container(self, sprites: &SpriteStorage, definitions: &AssemblyDefinitions) -> Option<ItemIOContainer> {
+                match self {
+                    $(AssemblyType::$assembly_name => {
+                        Some($bundle::default_with_sprites(sprites).assembly_items)
+                    })*,
+                    AssemblyType::Custom(id) => {
+                        let definition = definitions.get(id)?;
+                        Some(DefinedAssemblyBundle::from_definition(definition, &AssemblyIcons::default()).assembly_items)
+                    }
+                }
+            }
+
+            pub fn get_recipes(self, sprites: &SpriteStorage, definitions: &AssemblyDefinitions) -> Option<AssemblyRecipes> {
+                match self {
+                    $(AssemblyType::$assembly_name => {
+                        Some($bundle::default_with_sprites(sprites).recipes)
+                    })*,
+                    AssemblyType::Custom(id) => {
+                        let definition = definitions.get(id)?;
+                        Some(AssemblyRecipes::new(definition.recipes.clone()))
+                    }
                 }
             }
         }
@@ -93,6 +138,7 @@ macro_rules! make_assembly_types {
         paste! {
             pub fn selected_assembly_hover(
                 $(mut [<ev_ $assembly_name:snake>]: EventWriter<ShowHoverGhost<$bundle>>,)*
+                mut ev_defined: EventWriter<ShowDefinedAssemblyGhost>,
                 selected: Res<SelectedAssembly>,
             ){
                 match selected.selected {
@@ -102,6 +148,9 @@ macro_rules! make_assembly_types {
                             bundle: PhantomData::<$bundle>
                         });
                     },)*
+                    AssemblyType::Custom(id) => {
+                        ev_defined.send(ShowDefinedAssemblyGhost { id });
+                    }
                 }
             }
         }
@@ -109,6 +158,7 @@ macro_rules! make_assembly_types {
         paste! {
             pub fn update_assembly_ghost(
                 $(mut [<ev_ $assembly_name:snake>]: EventWriter<ShowHoverGhost<$bundle>>,)*
+                mut ev_defined: EventWriter<ShowDefinedAssemblyGhost>,
                 mut ev_hide_ghost: EventWriter<HideHoverGhost>,
                 selected: Res<SelectedAssembly>,
             ){
@@ -121,6 +171,9 @@ macro_rules! make_assembly_types {
                                 bundle: PhantomData::<$bundle>
                             });
                         },)*
+                        AssemblyType::Custom(id) => {
+                            ev_defined.send(ShowDefinedAssemblyGhost { id });
+                        }
                     }
                 }
             }
@@ -1,3 +1,4 @@
+use std::marker::PhantomData;
 
 use crate::*;
 
@@ -13,6 +14,12 @@ pub use assembly_types::*;
 mod assembly_production;
 pub use assembly_production::*;
 
+mod assembly_definitions;
+pub use assembly_definitions::*;
+
+mod power_grid;
+pub use power_grid::*;
+
 use self::assembly_types::assembly_templates::*;
 
 pub struct AssembliesPlugin;
@@ -35,6 +42,13 @@ impl Plugin for AssembliesPlugin {
             .add_event::<ShowHoverGhost::<PulpMachineBundle>>()
             .add_event::<ShowHoverGhost::<PaperMachineBundle>>()
             .add_event::<ShowHoverGhost::<SawMillBundle>>()
+            .add_systems(Update, show_defined_assembly_ghost)
+            .add_event::<ShowDefinedAssemblyGhost>()
+            .init_asset::<AssemblyDefinition>()
+            .init_asset_loader::<AssemblyDefinitionLoader>()
+            .init_resource::<AssemblyDefinitions>()
+            .add_systems(Startup, load_assembly_definitions)
+            .add_systems(Update, register_assembly_definitions)
             .add_systems(Update,
             (
                     (place_assembly).run_if(in_state(PlayerState::Assemblies)).run_if(in_state(PlacementState::Allowed)),
@@ -45,7 +59,8 @@ impl Plugin for AssembliesPlugin {
             .add_systems(Update,
                 (
                     produce_goods,
-                    add_assembly_power_input,
+                    select_assembly_recipe,
+                    (update_power_grids, add_assembly_power_input, generate_power).chain(),
                     show_assembly_progress_bars,
                     update_assembly_progress_bars,
                     assembly_power_display
@@ -60,6 +75,32 @@ impl Plugin for AssembliesPlugin {
             .add_event::<GenericMouseCollisionEvent::<ContainerInputSelector>>()
             .add_event::<GenericMouseCollisionEvent::<ContainerOutputSelector>>()
             .add_event::<AssemblyPowerInput>()
+            .add_systems(OnEnter(PlayerState::Cables),
+                |mut ev_show_ghost: EventWriter<ShowHoverGhost<PowerCableBundle>>| {
+                    ev_show_ghost.send(ShowHoverGhost::<PowerCableBundle> {
+                        bundle: PhantomData::<PowerCableBundle>
+                    });
+                }
+            )
+            .add_systems(OnExit(PlayerState::Cables),
+                |mut ev_hide_ghost: EventWriter<HideHoverGhost>| {
+                    ev_hide_ghost.send(HideHoverGhost);
+                }
+            )
+            .add_systems(Update, show_hover_ghost::<PowerCableBundle>)
+            .add_event::<ShowHoverGhost::<PowerCableBundle>>()
+            .add_systems(Update, (
+                (place_power_cable).run_if(in_state(PlayerState::Cables)).run_if(in_state(PlacementState::Allowed)),
+                (cycle_cable_placement).run_if(in_state(PlayerState::Cables)),
+                input_toggle_cable_mode,
+                refund_power_cable,
+            ).run_if(in_state(DayCycleState::Day)))
+            .add_systems(PreUpdate, mouse_collision_system::<PowerCable>)
+            .add_event::<GenericMouseCollisionEvent::<PowerCable>>()
+            .insert_resource(PowerGrids::default())
+            .insert_resource(PowerCableSettings::default())
+            .insert_resource(PowerCablePlacement::default())
+            .add_event::<SelectRecipeEvent>()
             .register_type::<ItemIOContainer>()
             .register_type::<AssemblyPower>()
             .insert_resource(SelectedAssembly::default())
@@ -1,12 +1,40 @@
 use crate::*;
 
-#[derive(Component, Clone, Copy, Debug, Reflect, PartialEq)]
+#[derive(Component, Clone, Copy, Debug, Reflect, PartialEq, Serialize, Deserialize)]
 pub enum Power {
     Mechanical(f32),
     Thermal(f32),
     Electrical(f32)
 }
 
+impl Power {
+    pub fn amount(self) -> f32 {
+        match self {
+            Power::Mechanical(a) | Power::Thermal(a) | Power::Electrical(a) => a
+        }
+    }
+
+    pub fn with_amount(self, amount: f32) -> Power {
+        match self {
+            Power::Mechanical(_) => Power::Mechanical(amount),
+            Power::Thermal(_) => Power::Thermal(amount),
+            Power::Electrical(_) => Power::Electrical(amount),
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Power::Mechanical(_) => "Mechanical",
+            Power::Thermal(_) => "Thermal",
+            Power::Electrical(_) => "Electrical",
+        }
+    }
+
+    pub fn is_same_type(self, other: Power) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+}
+
 impl std::ops::Sub for Power {
     type Output = Power;
 
@@ -70,50 +98,47 @@ pub struct AssemblyPowerInput {
 
 pub fn add_assembly_power_input(
     mut ev_power_input: EventReader<AssemblyPowerInput>,
-    mut q_assembly_power: Query<&mut AssemblyPower>,
-    mut q_job_error: Query<&mut JobError>
+    mut q_assembly_power: Query<(Entity, &mut AssemblyPower)>,
+    mut q_job_error: Query<&mut JobError>,
+    power_grids: Res<PowerGrids>,
 ) {
-    for mut assembly in q_assembly_power.iter_mut() {
+    for (entity, mut assembly) in q_assembly_power.iter_mut() {
         assembly.current_power = match assembly.current_power {
             Power::Mechanical(_) => Power::Mechanical(0.0),
             Power::Thermal(_) => Power::Thermal(0.0),
             Power::Electrical(_) => Power::Electrical(0.0)
         };
         assembly.powering_entities.clear();
+
+        // Grid power comes first, workers and the player top it up
+        // Grid supply is already converted to the machine's own power type
+        if let Some(supplied) = power_grids.supply.get(&entity) {
+            if supplied.is_same_type(assembly.current_power) {
+                assembly.current_power = supplied.with_amount(supplied.amount().min(assembly.max_power));
+            }
+        }
     }
-    for ev in ev_power_input.iter() {
-        if let Ok(mut assembly) = q_assembly_power.get_mut(ev.assembly) {
-            fn handle_power(input_power: Power, input_amount: f32, assembly: &mut AssemblyPower, source: Entity) {
-                if let Some(existing) = match (input_power, &assembly.current_power) {
-                    (Power::Electrical(_), Power::Electrical(e)) => Some(*e),
-                    (Power::Thermal(_), Power::Thermal(e)) => Some(*e),
-                    (Power::Mechanical(_), Power::Mechanical(e)) => Some(*e),
-                    _ => None,
-                } {
-                    if assembly.powering_entities.contains(&source) {
-                        return;
-                    }
-                    if existing + input_amount > assembly.max_power {
-                        assembly.current_power = input_power;
-                        return;
-                    }
-                    assembly.current_power = match input_power {
-                        Power::Electrical(_) => Power::Electrical(existing + input_amount),
-                        Power::Thermal(_) => Power::Thermal(existing + input_amount),
-                        Power::Mechanical(_) => Power::Mechanical(existing + input_amount),
-                    };
-                    assembly.powering_entities.push(source);
+    for ev in ev_power_input.read() {
+        if let Ok((_, mut assembly)) = q_assembly_power.get_mut(ev.assembly) {
+            fn handle_power(input_power: Power, assembly: &mut AssemblyPower, source: Entity) -> Result<(), String> {
+                let Some(efficiency) = assembly.get_efficiency(input_power) else {
+                    return Err(format!("Expected {} power, got {}", assembly.get_accepted_names(), input_power.get_name()));
+                };
+                if assembly.powering_entities.contains(&source) {
+                    return Ok(());
                 }
+                let existing = assembly.current_power.amount();
+                let input_amount = input_power.amount() * efficiency;
+                assembly.current_power = assembly.current_power.with_amount((existing + input_amount).min(assembly.max_power));
+                assembly.powering_entities.push(source);
+                Ok(())
             }
 
-            match ev.power {
-                Power::Electrical(input_amount) => handle_power(Power::Electrical(input_amount), input_amount, &mut assembly, ev.source),
-                Power::Thermal(input_amount) => handle_power(Power::Thermal(input_amount), input_amount, &mut assembly, ev.source),
-                Power::Mechanical(input_amount) => handle_power(Power::Mechanical(input_amount), input_amount, &mut assembly, ev.source),
-            }
-
-            if let Ok(mut job_error) = q_job_error.get_mut(ev.source) {
-                job_error.set_error("Wrong power type");
+            if let Err(err) = handle_power(ev.power, &mut assembly, ev.source) {
+                match q_job_error.get_mut(ev.source) {
+                    Ok(mut job_error) => job_error.set_error(&err),
+                    Err(_) => println!("{}", err),
+                }
             }
         }
     }
@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+
+use bevy::utils::{HashMap, HashSet};
+
+use crate::*;
+
+// Assemblies that supply power to connected grids, optionally burning fuel from their input
+#[derive(Component, Clone, Debug, Reflect, Deserialize)]
+pub struct PowerGenerator {
+    pub output: Power,
+    #[serde(default)]
+    pub fuel: Option<Item>,
+    // Seconds one fuel item lasts
+    #[serde(default)]
+    pub burn_time: f32,
+    #[serde(skip)]
+    pub remaining_fuel: f32,
+    // What is currently being supplied, scaled by fuel or input power
+    #[serde(skip)]
+    pub current_output: f32,
+}
+
+// Shafts carry mechanical power, pipes thermal and cables electrical
+#[derive(Component, Debug)]
+pub struct PowerCable {
+    pub power_type: Power,
+}
+impl Clickable for PowerCable {}
+
+#[derive(Resource, Reflect)]
+pub struct PowerCableSettings {
+    pub price: f32,
+}
+impl Default for PowerCableSettings {
+    fn default() -> Self {
+        Self {
+            price: 5.0
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PowerCablePlacement {
+    pub power_type: Power,
+}
+impl Default for PowerCablePlacement {
+    fn default() -> Self {
+        Self {
+            power_type: Power::Mechanical(0.0)
+        }
+    }
+}
+
+pub fn get_cable_color(power_type: Power) -> Color {
+    match power_type {
+        Power::Mechanical(_) => Color::rgb(0.45, 0.3, 0.15),
+        Power::Thermal(_) => Color::rgb(0.85, 0.35, 0.1),
+        Power::Electrical(_) => Color::rgb(0.95, 0.85, 0.2),
+    }
+}
+
+#[derive(Bundle)]
+pub struct PowerCableBundle {
+    pub cable: PowerCable,
+    pub tile_size: EntityTileSize,
+    pub sprite: SpriteBundle,
+}
+impl GetGhostBundle for PowerCableBundle {
+    fn get_sprite_bundle(&self) -> Option<SpriteBundle> {
+        Some(self.sprite.clone())
+    }
+    fn get_tile_size(&self) -> Option<EntityTileSize> {
+        Some(self.tile_size)
+    }
+}
+impl DefaultWithSprites for PowerCableBundle {
+    fn default_with_sprites(_sprites: &SpriteStorage) -> Self {
+        PowerCableBundle {
+            cable: PowerCable {
+                power_type: Power::Mechanical(0.0)
+            },
+            tile_size: EntityTileSize(IVec2::new(1, 1)),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: get_cable_color(Power::Mechanical(0.0)),
+                    custom_size: Some(Vec2::new(TILE_SIZE.x * 0.5, TILE_SIZE.y * 0.5)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                ..default()
+            },
+        }
+    }
+}
+
+pub fn spawn_power_cable(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    pos: Vec2,
+    power_type: Power,
+) -> Entity {
+    let mut bundle = PowerCableBundle::default_with_sprites(sprites);
+    bundle.cable.power_type = power_type * 0.0;
+    bundle.sprite.sprite.color = get_cable_color(power_type);
+    bundle.sprite.transform.translation = Vec3::new(pos.x, pos.y, bundle.sprite.transform.translation.z);
+    commands.spawn(bundle).id()
+}
+
+pub fn input_toggle_cable_mode(
+    input: Res<Input<KeyCode>>,
+    state: Res<State<PlayerState>>,
+    mut next_state: ResMut<NextState<PlayerState>>
+) {
+    if input.just_pressed(KeyCode::V) {
+        if state.get() == &PlayerState::Cables {
+            next_state.set(PlayerState::None);
+        } else {
+            next_state.set(PlayerState::Cables);
+        }
+    }
+}
+
+pub fn cycle_cable_placement(
+    input: Res<Input<KeyCode>>,
+    mut placement: ResMut<PowerCablePlacement>,
+    mut q_ghost: Query<&mut Sprite, With<HoverGhost>>,
+) {
+    if input.just_pressed(KeyCode::R) {
+        placement.power_type = match placement.power_type {
+            Power::Mechanical(_) => Power::Thermal(0.0),
+            Power::Thermal(_) => Power::Electrical(0.0),
+            Power::Electrical(_) => Power::Mechanical(0.0),
+        };
+    }
+    for mut sprite in q_ghost.iter_mut() {
+        sprite.color = get_cable_color(placement.power_type).with_a(0.5);
+    }
+}
+
+pub fn place_power_cable(
+    mut commands: Commands,
+    input: Res<Input<MouseButton>>,
+    mouse_tile: Res<MouseTile>,
+    settings: Res<PowerCableSettings>,
+    placement: Res<PowerCablePlacement>,
+    mut money: ResMut<PlayerMoney>,
+    collision_grid: Res<CollisionGrid>,
+    q_cables: Query<&Transform, With<PowerCable>>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform), Without<PowerCable>>,
+    sprites: Res<SpriteStorage>,
+) {
+    if input.just_pressed(MouseButton::Left) {
+        let (grid_size, map_type, map_transform) = tilemap_q.single();
+        let pos = get_tile_world_pos(&mouse_tile.0, map_transform, grid_size, map_type);
+        if collision_grid.is_solid(&mouse_tile.0)
+            || q_cables.iter().any(|t| t.translation.xy().distance(pos) < 1.0) {
+            println!("Can't place cable here");
+            return;
+        }
+        let Ok(_) = money.try_remove_money(settings.price) else {
+            println!("Not enough money to place cable");
+            return
+        };
+        spawn_power_cable(&mut commands, &sprites, pos, placement.power_type);
+    }
+}
+
+pub fn refund_power_cable(
+    mut commands: Commands,
+    mut ev_cable_mouse: EventReader<GenericMouseCollisionEvent<PowerCable>>,
+    input: Res<Input<KeyCode>>,
+    settings: Res<PowerCableSettings>,
+    mut money: ResMut<PlayerMoney>,
+) {
+    if input.just_pressed(KeyCode::Delete) {
+        let Some(ev) = ev_cable_mouse.read().next() else { return };
+        let Some((_, cable)) = ev.collision else { return };
+        money.add_money(settings.price);
+        commands.entity(cable).despawn_recursive();
+    }
+}
+
+pub struct PowerNetwork {
+    pub power_type: Power,
+    pub cables: Vec<Entity>,
+    pub supply: f32,
+    pub demand: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct PowerGrids {
+    pub networks: Vec<PowerNetwork>,
+    // Power each assembly receives from the grids it is connected to
+    pub supply: HashMap<Entity, Power>,
+}
+
+fn get_neighbor_tiles(tile: TilePos, map_size: &TilemapSize) -> Vec<TilePos> {
+    let mut neighbors = Vec::new();
+    if tile.x > 0 { neighbors.push(TilePos { x: tile.x - 1, y: tile.y }) }
+    if tile.y > 0 { neighbors.push(TilePos { x: tile.x, y: tile.y - 1 }) }
+    if tile.x + 1 < map_size.x { neighbors.push(TilePos { x: tile.x + 1, y: tile.y }) }
+    if tile.y + 1 < map_size.y { neighbors.push(TilePos { x: tile.x, y: tile.y + 1 }) }
+    neighbors
+}
+
+pub fn update_power_grids(
+    mut power_grids: ResMut<PowerGrids>,
+    q_cables: Query<(Entity, &PowerCable, &Transform)>,
+    q_assemblies: Query<(Entity, &Transform, Option<&EntityTileSize>, Option<&AssemblyPower>, Option<&PowerGenerator>), With<Assembly>>,
+    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &Transform), (Without<PowerCable>, Without<Assembly>)>,
+) {
+    power_grids.networks.clear();
+    power_grids.supply.clear();
+    let Ok((map_size, grid_size, map_type, map_transform)) = q_tilemap.get_single() else { return };
+
+    let mut cable_tiles = HashMap::new();
+    for (entity, cable, transform) in q_cables.iter() {
+        let Some(tile) = get_entity_tiles(transform, None, map_size, grid_size, map_type, map_transform).first().copied() else { continue };
+        cable_tiles.insert(tile, (entity, cable.power_type));
+    }
+
+    // Flood fill connected cables of the same power type into networks
+    let mut tile_networks: HashMap<TilePos, usize> = HashMap::new();
+    let mut networks = Vec::new();
+    for (start, (_, power_type)) in cable_tiles.iter() {
+        if tile_networks.contains_key(start) { continue }
+        let network_i = networks.len();
+        let mut network = PowerNetwork {
+            power_type: *power_type,
+            cables: Vec::new(),
+            supply: 0.0,
+            demand: 0.0,
+        };
+        let mut queue = VecDeque::from([*start]);
+        tile_networks.insert(*start, network_i);
+        while let Some(tile) = queue.pop_front() {
+            let Some((entity, _)) = cable_tiles.get(&tile) else { continue };
+            network.cables.push(*entity);
+            for neighbor in get_neighbor_tiles(tile, map_size) {
+                let Some((_, neighbor_type)) = cable_tiles.get(&neighbor) else { continue };
+                if !neighbor_type.is_same_type(*power_type) || tile_networks.contains_key(&neighbor) { continue }
+                tile_networks.insert(neighbor, network_i);
+                queue.push_back(neighbor);
+            }
+        }
+        networks.push(network);
+    }
+
+    // Assemblies connect to any network touching or under their footprint
+    let mut consumers: Vec<Vec<(Entity, Power, f32, f32)>> = networks.iter().map(|_| Vec::new()).collect();
+    for (entity, transform, tile_size, power, generator) in q_assemblies.iter() {
+        let mut connected = HashSet::new();
+        for tile in get_entity_tiles(transform, tile_size, map_size, grid_size, map_type, map_transform) {
+            for neighbor in get_neighbor_tiles(tile, map_size).into_iter().chain([tile]) {
+                if let Some(network_i) = tile_networks.get(&neighbor) {
+                    connected.insert(*network_i);
+                }
+            }
+        }
+
+        if let Some(generator) = generator {
+            let outputs = connected.iter()
+                .filter(|i| networks[**i].power_type.is_same_type(generator.output))
+                .copied()
+                .collect::<Vec<_>>();
+            for network_i in outputs.iter() {
+                networks[*network_i].supply += generator.current_output / outputs.len() as f32;
+            }
+        }
+        if let Some(power) = power {
+            if power.power_cost <= 0.0 { continue }
+            for network_i in connected.iter() {
+                let network_type = networks[*network_i].power_type;
+                // Converters don't draw from the networks they feed
+                if generator.is_some_and(|generator| generator.output.is_same_type(network_type)) { continue }
+                let Some(efficiency) = power.get_efficiency(network_type) else { continue };
+                networks[*network_i].demand += power.power_cost;
+                consumers[*network_i].push((entity, power.current_power, efficiency, power.power_cost));
+            }
+        }
+    }
+
+    // Supply is shared out by how much each machine needs
+    for (network, consumers) in networks.iter().zip(consumers.iter()) {
+        if network.demand <= 0.0 { continue }
+        for (entity, machine_power, efficiency, cost) in consumers.iter() {
+            let amount = network.supply * cost / network.demand * efficiency;
+            let supplied = power_grids.supply.entry(*entity).or_insert(machine_power.with_amount(0.0));
+            *supplied = *supplied + machine_power.with_amount(amount);
+        }
+    }
+    power_grids.networks = networks;
+}
+
+pub fn generate_power(
+    mut commands: Commands,
+    mut q_generators: Query<(Entity, &mut PowerGenerator, Option<&mut ItemIOContainer>, Option<&AssemblyPower>)>,
+    q_items: Query<&Item>,
+    time: Res<Time>,
+) {
+    for (entity, mut generator, io_container, power) in q_generators.iter_mut() {
+        let max_output = generator.output.amount();
+        let Some(fuel) = generator.fuel else {
+            // Converters run off the power they are given
+            generator.current_output = match power {
+                Some(power) if power.power_cost > 0.0 => max_output * (power.current_power.amount() / power.power_cost).min(1.0),
+                _ => max_output
+            };
+            continue;
+        };
+
+        if generator.remaining_fuel <= 0.0 {
+            generator.current_output = 0.0;
+            let Some(mut io_container) = io_container else { continue };
+            let fuel_item = io_container.input.items.iter()
+                .filter_map(|item| *item)
+                .find(|item| q_items.get(*item).is_ok_and(|item| *item == fuel));
+            let Some(fuel_item) = fuel_item else { continue };
+            if io_container.input.remove_item(Some(fuel_item)).is_ok() {
+                commands.entity(entity).remove_children(&[fuel_item]);
+                commands.entity(fuel_item).insert(DespawnLater);
+                generator.remaining_fuel = generator.burn_time;
+            }
+        }
+        if generator.remaining_fuel > 0.0 {
+            generator.remaining_fuel -= time.delta_seconds();
+            generator.current_output = max_output;
+        }
+    }
+}
@@ -44,6 +44,13 @@ pub fn day_timer_system(
     }
 }
 
+pub fn advance_day(
+    mut day_timer: ResMut<DayTimer>,
+) {
+    day_timer.day_timer.reset();
+    day_timer.day_count += 1;
+}
+
 #[derive(Component, Clone, PartialEq, Default)]
 pub struct NightUIProps {
     pub image: Handle<Image>,
@@ -151,9 +158,43 @@ pub fn night_ui_render(
                             ..default()
                         }}
                     />
-                    <ImportsSelectionBundle
+                    <ElementBundle
+                        styles={KStyle {
+                            layout_type: LayoutType::Row.into(),
+                            ..default()
+                        }}
+                    >
+                        <ImportsSelectionBundle
+                            styles={KStyle {
+                                left: Units::Pixels(50.0).into(),
+                                ..default()
+                            }}
+                        />
+                        <PriceHistoryChartBundle
+                            styles={KStyle {
+                                left: Units::Pixels(20.0).into(),
+                                ..default()
+                            }}
+                        />
+                    </ElementBundle>
+                    <HiringBoardBundle
+                        styles={KStyle {
+                            left: Units::Pixels(50.0).into(),
+                            top: Units::Pixels(20.0).into(),
+                            ..default()
+                        }}
+                    />
+                    <ContractBoardBundle
                         styles={KStyle {
                             left: Units::Pixels(50.0).into(),
+                            top: Units::Pixels(20.0).into(),
+                            ..default()
+                        }}
+                    />
+                    <ExportRulesBundle
+                        styles={KStyle {
+                            left: Units::Pixels(50.0).into(),
+                            top: Units::Pixels(20.0).into(),
                             ..default()
                         }}
                     />
@@ -390,6 +431,629 @@ pub fn imports_selection_render(
     true
 }
 
+const PRICE_CHART_HEIGHT: f32 = 120.0;
+const PRICE_CHART_BAR_WIDTH: f32 = 12.0;
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct PriceHistoryChart;
+impl Widget for PriceHistoryChart {}
+
+#[derive(Bundle)]
+pub struct PriceHistoryChartBundle {
+    pub props: PriceHistoryChart,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for PriceHistoryChartBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..Default::default()
+            },
+            computed_styles: Default::default(),
+            widget_name: PriceHistoryChart::default().get_name(),
+        }
+    }
+}
+
+// One bar per day for the selected item, scaled to the highest price in the window
+pub fn price_history_chart_render(
+    In(entity): In<Entity>,
+    widget_context: Res<KayakWidgetContext>,
+    mut commands: Commands,
+    mut query: Query<(&mut ComputedStyles, &KStyle)>,
+    economy: Res<Economy>,
+    price_history: Res<PriceHistory>,
+) -> bool {
+    if let Ok((mut computed_styles, base_style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(base_style)
+        .into();
+
+        let parent_id = Some(entity);
+
+        let mut items = economy.prices.keys().copied().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        let selected = price_history.selected.or(items.first().copied());
+        let records = selected.and_then(|item| price_history.get(&item))
+            .map(|records| records.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let max_price = records.iter().map(|record| record.price).fold(0.01, f32::max);
+        let summary = match (records.first(), records.last()) {
+            (Some(first), Some(last)) => {
+                let low = records.iter().map(|record| record.price).fold(f32::MAX, f32::min);
+                let trend = if last.price > first.price { "up" } else if last.price < first.price { "down" } else { "flat" };
+                format!(
+                    "${:.2} today, low ${:.2}, high ${:.2}, {} since day {} | Supply {:.0} Demand {:.0}",
+                    last.price, low, max_price, trend, first.day, last.supply, last.demand
+                )
+            },
+            _ => "No history yet".to_string(),
+        };
+
+        rsx!(
+            <ElementBundle
+                styles={KStyle {
+                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(65, 68, 90)),
+                    ..Default::default()
+                }}
+            >
+                <TextWidgetBundle
+                    text={TextProps {
+                        content: "Price history".to_string(),
+                        ..Default::default()
+                    }}
+                />
+                <ElementBundle
+                    styles={KStyle {
+                        layout_type: LayoutType::Row.into(),
+                        height: Units::Pixels(32.0).into(),
+                        ..Default::default()
+                    }}
+                >
+                    {
+                        for item in items.iter().copied() {
+                            let select_click = OnEvent::new(
+                                move |In(_entity): In<Entity>,
+                                      event: ResMut<KEvent>,
+                                      mut price_history: ResMut<PriceHistory>| {
+                                    if let EventType::Click(_) = event.event_type {
+                                        price_history.selected = Some(item);
+                                    }
+                                },
+                            );
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        width: Units::Pixels(120.0).into(),
+                                        ..Default::default()
+                                    }}
+                                    on_event={select_click}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: item.get_name().to_string(),
+                                            ..Default::default()
+                                        }}
+                                        styles={KStyle {
+                                            color: if Some(item) == selected { Color::WHITE.into() } else { Color::GRAY.into() },
+                                            font_size: StyleProp::<f32>::Value(21.0),
+                                            ..Default::default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            );
+                        }
+                    }
+                </ElementBundle>
+                <ElementBundle
+                    styles={KStyle {
+                        layout_type: LayoutType::Row.into(),
+                        height: Units::Pixels(PRICE_CHART_HEIGHT).into(),
+                        width: Units::Pixels((PRICE_CHART_BAR_WIDTH + 2.0) * PRICE_HISTORY_DAYS as f32).into(),
+                        ..Default::default()
+                    }}
+                >
+                    {
+                        for (i, record) in records.iter().enumerate() {
+                            let height = (record.price / max_price * PRICE_CHART_HEIGHT).max(1.0);
+                            // The latest day stands out from the rest
+                            let color = if i + 1 == records.len() { Color::GREEN } else { Color::rgb_u8(150, 160, 220) };
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        background_color: StyleProp::<Color>::Value(color),
+                                        width: Units::Pixels(PRICE_CHART_BAR_WIDTH).into(),
+                                        height: Units::Pixels(height).into(),
+                                        top: Units::Stretch(1.0).into(),
+                                        left: Units::Pixels(2.0).into(),
+                                        ..Default::default()
+                                    }}
+                                />
+                            );
+                        }
+                    }
+                </ElementBundle>
+                <TextWidgetBundle
+                    text={TextProps {
+                        content: summary,
+                        ..Default::default()
+                    }}
+                    styles={KStyle {
+                        font_size: StyleProp::<f32>::Value(21.0),
+                        ..Default::default()
+                    }}
+                />
+            </ElementBundle>
+        );
+    }
+    true
+}
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct ExportRules;
+impl Widget for ExportRules {}
+
+#[derive(Bundle)]
+pub struct ExportRulesBundle {
+    pub props: ExportRules,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for ExportRulesBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..Default::default()
+            },
+            computed_styles: Default::default(),
+            widget_name: ExportRules::default().get_name(),
+        }
+    }
+}
+
+pub fn export_rules_render(
+    In(entity): In<Entity>,
+    widget_context: Res<KayakWidgetContext>,
+    mut commands: Commands,
+    mut query: Query<(&mut ComputedStyles, &KStyle)>,
+    economy: Res<Economy>,
+    selected_depot: Res<SelectedExportDepot>,
+    q_depots: Query<(Entity, &ExportSellRules), With<ItemExport>>,
+) -> bool {
+    if let Ok((mut computed_styles, base_style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(base_style)
+        .into();
+
+        let parent_id = Some(entity);
+
+        let mut depots = q_depots.iter().collect::<Vec<_>>();
+        depots.sort_by_key(|(depot, _)| *depot);
+        let depot_count = depots.len();
+        let depot_index = selected_depot.index.min(depot_count.saturating_sub(1));
+        let Some((depot, rules)) = depots.get(depot_index).copied() else { return true };
+
+        let mut items = economy.prices.keys().copied().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        let next_depot_click = OnEvent::new(
+            move |In(_entity): In<Entity>,
+                  event: ResMut<KEvent>,
+                  mut selected_depot: ResMut<SelectedExportDepot>| {
+                if let EventType::Click(_) = event.event_type {
+                    selected_depot.index = (depot_index + 1) % depot_count.max(1);
+                }
+            },
+        );
+
+        rsx!(
+            <ElementBundle
+                styles={KStyle {
+                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(65, 68, 90)),
+                    ..Default::default()
+                }}
+            >
+                <ElementBundle
+                    styles={KStyle {
+                        layout_type: LayoutType::Row.into(),
+                        height: Units::Pixels(40.0).into(),
+                        ..Default::default()
+                    }}
+                >
+                    <TextWidgetBundle
+                        text={TextProps {
+                            content: format!("Export rules (depot {}/{})", depot_index + 1, depot_count),
+                            ..Default::default()
+                        }}
+                        styles={KStyle {
+                            width: Units::Pixels(420.0).into(),
+                            ..Default::default()
+                        }}
+                    />
+                    {
+                        if depot_count > 1 {
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        width: Units::Pixels(80.0).into(),
+                                        ..Default::default()
+                                    }}
+                                    on_event={next_depot_click}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: "Next".to_string(),
+                                            ..Default::default()
+                                        }}
+                                        styles={KStyle {
+                                            color: Color::GREEN.into(),
+                                            font_size: StyleProp::<f32>::Value(24.0),
+                                            ..Default::default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            );
+                        }
+                    }
+                </ElementBundle>
+                {
+                    for item in items.iter().copied() {
+                        let rule = rules.get(&item);
+                        constructor!(
+                            <BackgroundBundle
+                                styles={KStyle {
+                                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(50, 58, 108)),
+                                    layout_type: LayoutType::Row.into(),
+                                    ..default()
+                                }}
+                            >
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: format!("{}: {}", item.get_name(), rule.get_description()),
+                                        ..Default::default()
+                                    }}
+                                    styles={KStyle {
+                                        width: Units::Pixels(420.0).into(),
+                                        font_size: StyleProp::<f32>::Value(24.0),
+                                        ..Default::default()
+                                    }}
+                                />
+                                {
+                                    for button in SellRuleButton::ALL {
+                                        let button_click = OnEvent::new(
+                                            move |In(_entity): In<Entity>,
+                                                  event: ResMut<KEvent>,
+                                                  mut ev_edit: EventWriter<EditSellRuleEvent>| {
+                                                if let EventType::Click(_) = event.event_type {
+                                                    ev_edit.send(EditSellRuleEvent { depot, item, button });
+                                                }
+                                            },
+                                        );
+                                        let active = button == SellRuleButton::Hold && rule.hold;
+                                        constructor!(
+                                            <ElementBundle
+                                                styles={KStyle {
+                                                    width: Units::Pixels(60.0).into(),
+                                                    ..default()
+                                                }}
+                                                on_event={button_click}
+                                            >
+                                                <TextWidgetBundle
+                                                    text={TextProps {
+                                                        content: button.get_name().to_string(),
+                                                        ..Default::default()
+                                                    }}
+                                                    styles={KStyle {
+                                                        color: if active { Color::YELLOW.into() } else { Color::GREEN.into() },
+                                                        font_size: StyleProp::<f32>::Value(24.0),
+                                                        ..Default::default()
+                                                    }}
+                                                />
+                                            </ElementBundle>
+                                        );
+                                    }
+                                }
+                            </BackgroundBundle>
+                        );
+                    }
+                }
+            </ElementBundle>
+        );
+    }
+    true
+}
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct HiringBoard;
+impl Widget for HiringBoard {}
+
+#[derive(Bundle)]
+pub struct HiringBoardBundle {
+    pub props: HiringBoard,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for HiringBoardBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..Default::default()
+            },
+            computed_styles: Default::default(),
+            widget_name: HiringBoard::default().get_name(),
+        }
+    }
+}
+
+pub fn hiring_board_render(
+    In(entity): In<Entity>,
+    widget_context: Res<KayakWidgetContext>,
+    mut commands: Commands,
+    mut query: Query<(&mut ComputedStyles, &KStyle)>,
+    candidates: Res<WorkerCandidates>,
+) -> bool {
+    if let Ok((mut computed_styles, base_style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(base_style)
+        .into();
+
+        let parent_id = Some(entity);
+
+        rsx!(
+            <ElementBundle
+                styles={KStyle {
+                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(65, 68, 90)),
+                    ..Default::default()
+                }}
+            >
+                <TextWidgetBundle
+                    text={TextProps {
+                        content: "Hiring".to_string(),
+                        ..Default::default()
+                    }}
+                />
+                {
+                    for (i, candidate) in candidates.candidates.iter().enumerate() {
+                        let hire_click = OnEvent::new(
+                            move |In(_entity): In<Entity>,
+                                  event: ResMut<KEvent>,
+                                  mut ev_hire: EventWriter<HireWorkerEvent>| {
+                                if let EventType::Click(_) = event.event_type {
+                                    ev_hire.send(HireWorkerEvent { candidate: i });
+                                }
+                            },
+                        );
+                        constructor!(
+                            <BackgroundBundle
+                                styles={KStyle {
+                                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(50, 58, 108)),
+                                    layout_type: LayoutType::Row.into(),
+                                    ..default()
+                                }}
+                            >
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: format!(
+                                            "{} ({}) {}",
+                                            candidate.profile.name,
+                                            candidate.profile.get_traits_description(),
+                                            candidate.stats.get_description()
+                                        ),
+                                        ..Default::default()
+                                    }}
+                                    styles={KStyle {
+                                        width: Units::Pixels(520.0).into(),
+                                        font_size: StyleProp::<f32>::Value(24.0),
+                                        ..Default::default()
+                                    }}
+                                />
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: format!("${:.2} + ${:.2}/day", candidate.get_hire_price(), candidate.stats.wage),
+                                        ..Default::default()
+                                    }}
+                                    styles={KStyle {
+                                        width: Units::Pixels(200.0).into(),
+                                        font_size: StyleProp::<f32>::Value(24.0),
+                                        ..Default::default()
+                                    }}
+                                />
+                                <ElementBundle
+                                    styles={KStyle {
+                                        width: Units::Pixels(60.0).into(),
+                                        ..default()
+                                    }}
+                                    on_event={hire_click}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: "Hire".to_string(),
+                                            ..Default::default()
+                                        }}
+                                        styles={KStyle {
+                                            color: Color::GREEN.into(),
+                                            font_size: StyleProp::<f32>::Value(24.0),
+                                            ..Default::default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            </BackgroundBundle>
+                        );
+                    }
+                }
+                {
+                    if !candidates.hired.is_empty() {
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: format!("{} hired, starting tomorrow", candidates.hired.len()),
+                                    ..Default::default()
+                                }}
+                                styles={KStyle {
+                                    font_size: StyleProp::<f32>::Value(24.0),
+                                    ..Default::default()
+                                }}
+                            />
+                        );
+                    }
+                }
+            </ElementBundle>
+        );
+    }
+    true
+}
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct ContractBoard;
+impl Widget for ContractBoard {}
+
+#[derive(Bundle)]
+pub struct ContractBoardBundle {
+    pub props: ContractBoard,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for ContractBoardBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..Default::default()
+            },
+            computed_styles: Default::default(),
+            widget_name: ContractBoard::default().get_name(),
+        }
+    }
+}
+
+pub fn contract_board_render(
+    In(entity): In<Entity>,
+    widget_context: Res<KayakWidgetContext>,
+    mut commands: Commands,
+    mut query: Query<(&mut ComputedStyles, &KStyle)>,
+    contracts: Res<Contracts>,
+) -> bool {
+    if let Ok((mut computed_styles, base_style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(base_style)
+        .into();
+
+        let parent_id = Some(entity);
+
+        rsx!(
+            <ElementBundle
+                styles={KStyle {
+                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(65, 68, 90)),
+                    ..Default::default()
+                }}
+            >
+                <TextWidgetBundle
+                    text={TextProps {
+                        content: "Contracts".to_string(),
+                        ..Default::default()
+                    }}
+                />
+                {
+                    for (i, contract) in contracts.offers.iter().enumerate() {
+                        let accept_click = OnEvent::new(
+                            move |In(_entity): In<Entity>,
+                                  event: ResMut<KEvent>,
+                                  mut ev_accept: EventWriter<AcceptContractEvent>| {
+                                if let EventType::Click(_) = event.event_type {
+                                    ev_accept.send(AcceptContractEvent { offer: i });
+                                }
+                            },
+                        );
+                        constructor!(
+                            <BackgroundBundle
+                                styles={KStyle {
+                                    background_color: StyleProp::<Color>::Value(Color::rgb_u8(50, 58, 108)),
+                                    layout_type: LayoutType::Row.into(),
+                                    ..default()
+                                }}
+                            >
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: contract.get_description(),
+                                        ..Default::default()
+                                    }}
+                                    styles={KStyle {
+                                        width: Units::Pixels(520.0).into(),
+                                        font_size: StyleProp::<f32>::Value(24.0),
+                                        ..Default::default()
+                                    }}
+                                />
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: format!("-${:.2} if late", contract.penalty),
+                                        ..Default::default()
+                                    }}
+                                    styles={KStyle {
+                                        width: Units::Pixels(200.0).into(),
+                                        font_size: StyleProp::<f32>::Value(24.0),
+                                        ..Default::default()
+                                    }}
+                                />
+                                <ElementBundle
+                                    styles={KStyle {
+                                        width: Units::Pixels(80.0).into(),
+                                        ..default()
+                                    }}
+                                    on_event={accept_click}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: "Accept".to_string(),
+                                            ..Default::default()
+                                        }}
+                                        styles={KStyle {
+                                            color: Color::GREEN.into(),
+                                            font_size: StyleProp::<f32>::Value(24.0),
+                                            ..Default::default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            </BackgroundBundle>
+                        );
+                    }
+                    for contract in contracts.active.iter() {
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: format!("{} ({}/{} delivered)", contract.get_description(), contract.delivered, contract.amount),
+                                    ..Default::default()
+                                }}
+                                styles={KStyle {
+                                    font_size: StyleProp::<f32>::Value(24.0),
+                                    color: Color::YELLOW.into(),
+                                    ..Default::default()
+                                }}
+                            />
+                        );
+                    }
+                }
+            </ElementBundle>
+        );
+    }
+    true
+}
+
 #[derive(Resource, Default)]
 pub struct ImportSelections {
     pub selected: Vec<PurchasableItem>,
@@ -658,6 +1322,8 @@ pub fn render_revenue_summary(
     upkeep: Res<UpkeepTracker>,
     sold_items: Res<SoldItems>,
     unsold_items: Res<UnsoldItems>,
+    held_items: Res<HeldItems>,
+    contracts: Res<Contracts>,
     import_selections: Res<ImportSelections>,
     economy: Res<Economy>,
 ) -> bool {
@@ -725,6 +1391,20 @@ pub fn render_revenue_summary(
                 },
             );
 
+        let folded_held = held_items
+            .items
+            .iter()
+            .fold(
+                vec![],
+                |mut acc: Vec<(&str, &str, i32)>, (item, _, reason)| {
+                    match acc.iter().position(|x| x.0 == item.get_name() && x.1 == reason.get_name()) {
+                        Some(index) => acc[index].2 += 1,
+                        None => acc.push((item.get_name(), reason.get_name(), 1)),
+                    }
+                    acc
+                },
+            );
+
         let folded_upkeep = upkeep.upkeep.iter().fold(
             vec![],
             |mut acc: Vec<(&str, f32, i32)>, Upkeep(price, source)| {
@@ -742,10 +1422,16 @@ pub fn render_revenue_summary(
             },
         );
 
+        let total_contracts = contracts
+            .results
+            .iter()
+            .filter(|result| result.completed)
+            .fold(0.0, |acc, result| acc + result.contract.reward);
+
         let total_sold = sold_items
             .items
             .iter()
-            .fold(0.0, |acc, (_, price)| acc + price);
+            .fold(0.0, |acc, (_, price)| acc + price) + total_contracts;
 
         let total_upkeep = upkeep
             .upkeep
@@ -776,6 +1462,26 @@ pub fn render_revenue_summary(
                             />
                         );
                     }
+                    for result in contracts.results.iter() {
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: if result.completed {
+                                        format!("Contract {} x{}: {:.2}", result.contract.item.get_name(), result.contract.amount, result.contract.reward)
+                                    } else {
+                                        format!("Contract {} {}/{}: failed", result.contract.item.get_name(), result.contract.delivered, result.contract.amount)
+                                    },
+                                    ..default()
+                                }}
+                                styles={KStyle {
+                                    left: Units::Pixels(15.0).into(),
+                                    font_size: StyleProp::<f32>::Value(21.0),
+                                    color: if result.completed { Color::WHITE.into() } else { Color::RED.into() },
+                                    ..default()
+                                }}
+                            />
+                        );
+                    }
                     if unsold_items.items.len() > 0 {
                         constructor!(
                             <TextWidgetBundle
@@ -806,6 +1512,36 @@ pub fn render_revenue_summary(
                             );
                         }
                     }
+                    if !held_items.items.is_empty() {
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: "Held Items (sell rules):".to_string(),
+                                    ..default()
+                                }}
+                                styles={KStyle {
+                                    font_size: StyleProp::<f32>::Value(32.0),
+                                    color: Color::YELLOW.into(),
+                                    ..default()
+                                }}
+                            />
+                        );
+                        for (item, reason, count) in folded_held {
+                            constructor!(
+                                <TextWidgetBundle
+                                    text={TextProps {
+                                        content: format!("{:} x{:} ({})", item, count, reason),
+                                        ..default()
+                                    }}
+                                    styles={KStyle {
+                                        font_size: StyleProp::<f32>::Value(21.0),
+                                        color: Color::YELLOW.into(),
+                                        ..default()
+                                    }}
+                                />
+                            );
+                        }
+                    }
                 }
                 <TextWidgetBundle
                     text={TextProps {
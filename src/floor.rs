@@ -0,0 +1,330 @@
+use std::marker::PhantomData;
+
+use bevy::utils::HashMap;
+
+use crate::*;
+
+pub const WALL_PRICE: f32 = 4.0;
+pub const DOOR_PRICE: f32 = 20.0;
+
+pub struct FloorPlugin;
+
+impl Plugin for FloorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(PlayerState::Floor),
+                |mut ev_show_ghost: EventWriter<ShowHoverGhost<WallBundle>>| {
+                    ev_show_ghost.send(ShowHoverGhost::<WallBundle> {
+                        bundle: PhantomData::<WallBundle>
+                    });
+                }
+            )
+            .add_systems(OnExit(PlayerState::Floor),
+                |mut ev_hide_ghost: EventWriter<HideHoverGhost>| {
+                    ev_hide_ghost.send(HideHoverGhost);
+                }
+            )
+            .add_systems(Update, show_hover_ghost::<WallBundle>)
+            .add_event::<ShowHoverGhost::<WallBundle>>()
+            .add_systems(Update, (
+                (place_floor_tool).run_if(in_state(PlayerState::Floor)).run_if(in_state(PlacementState::Allowed)),
+                (cycle_floor_tool).run_if(in_state(PlayerState::Floor)),
+                input_toggle_floor_mode,
+            ).run_if(in_state(DayCycleState::Day)))
+            .add_systems(PostUpdate, apply_floor_zones.before(update_collision_grid))
+            .init_resource::<FloorPlacement>()
+            .init_resource::<FloorZones>();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum ZoneType {
+    Storage,
+    Production,
+    NoWalk,
+    Walkway,
+}
+impl ZoneType {
+    pub const ALL: [ZoneType; 4] = [ZoneType::Storage, ZoneType::Production, ZoneType::NoWalk, ZoneType::Walkway];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            ZoneType::Storage => "Storage",
+            ZoneType::Production => "Production",
+            ZoneType::NoWalk => "No-walk",
+            ZoneType::Walkway => "Walkway",
+        }
+    }
+
+    pub fn get_color(&self) -> Color {
+        match self {
+            ZoneType::Storage => Color::rgb(0.8, 0.8, 1.0),
+            ZoneType::Production => Color::rgb(1.0, 0.9, 0.7),
+            ZoneType::NoWalk => Color::rgb(1.0, 0.6, 0.6),
+            ZoneType::Walkway => Color::rgb(1.0, 1.0, 0.6),
+        }
+    }
+
+    // Relative to DEFAULT_TILE_COST, workers still cross no-walk zones when there is no way around
+    pub fn get_walk_cost(&self) -> u32 {
+        match self {
+            ZoneType::Storage => DEFAULT_TILE_COST * 2,
+            ZoneType::Production => DEFAULT_TILE_COST,
+            ZoneType::NoWalk => DEFAULT_TILE_COST * 25,
+            ZoneType::Walkway => DEFAULT_TILE_COST / 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloorTool {
+    Wall,
+    Door,
+    Zone(ZoneType),
+    Erase,
+}
+impl FloorTool {
+    pub fn next(self) -> Self {
+        match self {
+            FloorTool::Wall => FloorTool::Door,
+            FloorTool::Door => FloorTool::Zone(ZoneType::ALL[0]),
+            FloorTool::Zone(zone) => match ZoneType::ALL.iter().position(|z| *z == zone) {
+                Some(i) if i + 1 < ZoneType::ALL.len() => FloorTool::Zone(ZoneType::ALL[i + 1]),
+                _ => FloorTool::Erase,
+            },
+            FloorTool::Erase => FloorTool::Wall,
+        }
+    }
+
+    pub fn get_color(&self) -> Color {
+        match self {
+            FloorTool::Wall => Color::DARK_GRAY,
+            FloorTool::Door => Color::rgb(0.6, 0.4, 0.2),
+            FloorTool::Zone(zone) => zone.get_color(),
+            FloorTool::Erase => Color::RED,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct FloorPlacement {
+    pub tool: FloorTool
+}
+impl Default for FloorPlacement {
+    fn default() -> Self {
+        Self {
+            tool: FloorTool::Wall
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct FloorZones {
+    pub zones: HashMap<TilePos, ZoneType>
+}
+impl FloorZones {
+    pub fn get_nearest(&self, pos: TilePos, zone: ZoneType) -> Option<TilePos> {
+        self.zones.iter()
+            .filter(|(_, z)| **z == zone)
+            .map(|(tile, _)| *tile)
+            .min_by_key(|tile| {
+                let dx = tile.x as i64 - pos.x as i64;
+                let dy = tile.y as i64 - pos.y as i64;
+                dx * dx + dy * dy
+            })
+    }
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+// Doors keep walls closed to the eye but let the player and workers through
+#[derive(Component)]
+pub struct Door;
+
+#[derive(Bundle)]
+pub struct WallBundle {
+    pub wall: Wall,
+    pub solid: SolidEntity,
+    pub tile_size: EntityTileSize,
+    pub sprite: SpriteBundle,
+}
+impl GetGhostBundle for WallBundle {
+    fn get_sprite_bundle(&self) -> Option<SpriteBundle> {
+        Some(self.sprite.clone())
+    }
+    fn get_tile_size(&self) -> Option<EntityTileSize> {
+        Some(self.tile_size)
+    }
+}
+impl DefaultWithSprites for WallBundle {
+    fn default_with_sprites(_sprites: &SpriteStorage) -> Self {
+        WallBundle {
+            wall: Wall,
+            solid: SolidEntity,
+            tile_size: EntityTileSize(IVec2::new(1, 1)),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: FloorTool::Wall.get_color(),
+                    custom_size: Some(Vec2::new(TILE_SIZE.x, TILE_SIZE.y)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct DoorBundle {
+    pub door: Door,
+    pub tile_size: EntityTileSize,
+    pub sprite: SpriteBundle,
+}
+impl DefaultWithSprites for DoorBundle {
+    fn default_with_sprites(_sprites: &SpriteStorage) -> Self {
+        DoorBundle {
+            door: Door,
+            tile_size: EntityTileSize(IVec2::new(1, 1)),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: FloorTool::Door.get_color(),
+                    custom_size: Some(Vec2::new(TILE_SIZE.x, TILE_SIZE.y)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                ..default()
+            },
+        }
+    }
+}
+
+pub fn spawn_wall(commands: &mut Commands, sprites: &SpriteStorage, pos: Vec2) -> Entity {
+    let mut bundle = WallBundle::default_with_sprites(sprites);
+    bundle.sprite.transform.translation = Vec3::new(pos.x, pos.y, bundle.sprite.transform.translation.z);
+    commands.spawn(bundle).id()
+}
+
+pub fn spawn_door(commands: &mut Commands, sprites: &SpriteStorage, pos: Vec2) -> Entity {
+    let mut bundle = DoorBundle::default_with_sprites(sprites);
+    bundle.sprite.transform.translation = Vec3::new(pos.x, pos.y, bundle.sprite.transform.translation.z);
+    commands.spawn(bundle).id()
+}
+
+pub fn input_toggle_floor_mode(
+    input: Res<Input<KeyCode>>,
+    state: Res<State<PlayerState>>,
+    mut next_state: ResMut<NextState<PlayerState>>
+) {
+    if input.just_pressed(KeyCode::B) {
+        if state.get() == &PlayerState::Floor {
+            next_state.set(PlayerState::None);
+        } else {
+            next_state.set(PlayerState::Floor);
+        }
+    }
+}
+
+pub fn cycle_floor_tool(
+    input: Res<Input<KeyCode>>,
+    mut placement: ResMut<FloorPlacement>,
+    mut q_ghost: Query<&mut Sprite, With<HoverGhost>>,
+) {
+    if input.just_pressed(KeyCode::R) {
+        placement.tool = placement.tool.next();
+    }
+    for mut sprite in q_ghost.iter_mut() {
+        sprite.color = placement.tool.get_color().with_a(0.5);
+    }
+}
+
+// Holding the mouse paints the current tool along the cursor
+pub fn place_floor_tool(
+    mut commands: Commands,
+    input: Res<Input<MouseButton>>,
+    mouse_tile: Res<MouseTile>,
+    placement: Res<FloorPlacement>,
+    mut money: ResMut<PlayerMoney>,
+    mut floor_zones: ResMut<FloorZones>,
+    collision_grid: Res<CollisionGrid>,
+    q_structures: Query<(Entity, &Transform, Option<&Wall>), Or<(With<Wall>, With<Door>)>>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform), (Without<Wall>, Without<Door>)>,
+    sprites: Res<SpriteStorage>,
+) {
+    if !input.pressed(MouseButton::Left) {
+        return;
+    }
+    let (grid_size, map_type, map_transform) = tilemap_q.single();
+    let pos = get_tile_world_pos(&mouse_tile.0, map_transform, grid_size, map_type);
+    let structure = q_structures.iter().find(|(_, transform, _)| transform.translation.xy().distance(pos) < 1.0);
+
+    match placement.tool {
+        FloorTool::Wall | FloorTool::Door => {
+            if structure.is_some() || collision_grid.is_solid(&mouse_tile.0) {
+                if input.just_pressed(MouseButton::Left) {
+                    println!("Can't build here");
+                }
+                return;
+            }
+            let price = if placement.tool == FloorTool::Wall { WALL_PRICE } else { DOOR_PRICE };
+            let Ok(_) = money.try_remove_money(price) else {
+                if input.just_pressed(MouseButton::Left) {
+                    println!("Not enough money to build");
+                }
+                return
+            };
+            if placement.tool == FloorTool::Wall {
+                spawn_wall(&mut commands, &sprites, pos);
+            } else {
+                spawn_door(&mut commands, &sprites, pos);
+            }
+        },
+        FloorTool::Zone(zone) => {
+            if floor_zones.zones.get(&mouse_tile.0) != Some(&zone) {
+                floor_zones.zones.insert(mouse_tile.0, zone);
+            }
+        },
+        FloorTool::Erase => {
+            if floor_zones.zones.contains_key(&mouse_tile.0) {
+                floor_zones.zones.remove(&mouse_tile.0);
+            }
+            if let Some((entity, _, wall)) = structure {
+                money.add_money(if wall.is_some() { WALL_PRICE } else { DOOR_PRICE });
+                commands.entity(entity).despawn_recursive();
+            }
+        },
+    }
+}
+
+// Tints zone tiles and feeds their walk costs to the pathfinder
+pub fn apply_floor_zones(
+    floor_zones: Res<FloorZones>,
+    mut grid: ResMut<CollisionGrid>,
+    mut path_cache: ResMut<PathCache>,
+    mut painted: Local<Vec<TilePos>>,
+    q_tilemap: Query<&TileStorage>,
+    mut q_tiles: Query<&mut TileColor>,
+) {
+    if !floor_zones.is_changed() {
+        return;
+    }
+    grid.clear_costs();
+    for (tile, zone) in floor_zones.zones.iter() {
+        grid.set_cost(tile, zone.get_walk_cost());
+    }
+    path_cache.clear();
+
+    let Ok(tile_storage) = q_tilemap.get_single() else { return };
+    for tile in painted.drain(..) {
+        if let Some(mut color) = tile_storage.get(&tile).and_then(|entity| q_tiles.get_mut(entity).ok()) {
+            color.0 = Color::WHITE;
+        }
+    }
+    for (tile, zone) in floor_zones.zones.iter() {
+        if let Some(mut color) = tile_storage.get(tile).and_then(|entity| q_tiles.get_mut(entity).ok()) {
+            color.0 = zone.get_color();
+            painted.push(*tile);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use rand::{rngs::StdRng, thread_rng, Rng, RngCore, SeedableRng};
+
+use crate::*;
+
+pub struct GameRngPlugin {
+    pub seed: Option<u64>
+}
+impl GameRngPlugin {
+    // Usage: [--seed N]
+    pub fn from_args(args: &[String]) -> Self {
+        let seed = args.iter()
+            .position(|arg| arg == "--seed")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|seed| seed.parse().ok());
+        Self { seed }
+    }
+}
+
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(GameRng::new(self.seed.unwrap_or_else(|| thread_rng().gen())))
+            .init_resource::<SeedEntry>()
+            .add_systems(Update, input_game_seed.run_if(in_state(DayCycleState::Opening)))
+            .add_systems(OnExit(DayCycleState::Opening), start_game_rng);
+    }
+}
+
+// Every random draw in the game goes through this, so a run can be replayed from its seed
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+    // 32 bit words taken from rng, a save replays this many to carry on the same sequence
+    draws: u64,
+    // Set once a game is started or loaded, so the opening screen doesn't restart the sequence
+    started: bool,
+}
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            draws: 0,
+            started: false,
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_draws(&self) -> u64 {
+        self.draws
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    // Restarts the sequence from the beginning
+    pub fn start(&mut self, seed: u64) {
+        *self = GameRng::new(seed);
+        self.started = true;
+    }
+
+    // Picks the sequence up where a saved game left it
+    pub fn restore(&mut self, seed: u64, draws: u64) {
+        self.start(seed);
+        for _ in 0..draws {
+            self.next_u32();
+        }
+    }
+}
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.draws += 1;
+        self.rng.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.draws += 2;
+        self.rng.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.draws += (dest.len() as u64).div_ceil(4);
+        self.rng.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.draws += (dest.len() as u64).div_ceil(4);
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+// Digits typed on the opening screen, used as the seed when the game starts
+#[derive(Resource, Default)]
+pub struct SeedEntry {
+    pub digits: String
+}
+impl SeedEntry {
+    pub fn get_seed(&self) -> Option<u64> {
+        self.digits.parse().ok()
+    }
+}
+
+const DIGIT_KEYS: [KeyCode; 10] = [
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+
+// The seed can be typed in on the opening screen
+pub fn input_game_seed(
+    input: Res<Input<KeyCode>>,
+    mut seed_entry: ResMut<SeedEntry>,
+) {
+    for (digit, key) in DIGIT_KEYS.iter().enumerate() {
+        if input.just_pressed(*key) {
+            seed_entry.digits.push_str(&digit.to_string());
+            // Digits that would overflow a u64 are ignored
+            if seed_entry.get_seed().is_none() {
+                seed_entry.digits.pop();
+            }
+        }
+    }
+    if input.just_pressed(KeyCode::Back) {
+        seed_entry.digits.pop();
+    }
+}
+
+// Draws made before the game starts, like the first hiring board, are redone from the chosen seed
+pub fn start_game_rng(
+    mut game_rng: ResMut<GameRng>,
+    seed_entry: Res<SeedEntry>,
+    mut candidates: ResMut<WorkerCandidates>,
+) {
+    // A loaded game already carries on its saved sequence
+    if game_rng.is_started() {
+        return;
+    }
+    let seed = seed_entry.get_seed().unwrap_or(game_rng.get_seed());
+    game_rng.start(seed);
+    candidates.refresh(&mut *game_rng);
+}
@@ -0,0 +1,126 @@
+use bevy::{app::{AppExit, ScheduleRunnerPlugin}, input::InputPlugin, time::TimeUpdateStrategy};
+
+use crate::*;
+
+// Simulated seconds per frame, independent of how fast the machine runs
+const HEADLESS_TIMESTEP: f32 = 1.0 / 60.0;
+const HEADLESS_DEFAULT_DAYS: i32 = 30;
+const HEADLESS_SAVE_PATH: &str = "saves/headless.ron";
+
+#[derive(Resource, Clone)]
+pub struct HeadlessSettings {
+    pub days: i32,
+    pub load: Option<String>,
+    pub imports: Vec<PurchasableItem>,
+}
+impl Default for HeadlessSettings {
+    fn default() -> Self {
+        Self {
+            days: HEADLESS_DEFAULT_DAYS,
+            load: None,
+            imports: vec![PurchasableItem::Resource(ResourceItem::Wood); 5],
+        }
+    }
+}
+
+// Runs the factory without a window, renderer or UI
+// Usage: --headless [--days N] [--load path/to/save.ron] [--seed N]
+pub struct HeadlessPlugin {
+    pub settings: HeadlessSettings
+}
+impl HeadlessPlugin {
+    pub fn from_args(args: &[String]) -> Self {
+        let mut settings = HeadlessSettings::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--days" => {
+                    if let Some(days) = args.next().and_then(|d| d.parse().ok()) {
+                        settings.days = days;
+                    }
+                },
+                "--load" => {
+                    settings.load = args.next().cloned();
+                },
+                _ => {}
+            }
+        }
+        Self { settings }
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)))
+            .add_plugins((InputPlugin, TransformPlugin, HierarchyPlugin, AssetPlugin::default()))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(HEADLESS_TIMESTEP)))
+            .insert_resource(self.settings.clone())
+            // Keep simulation runs from overwriting the player's save
+            .insert_resource(SaveFile { path: HEADLESS_SAVE_PATH.to_string() })
+            // Item and machine sprites are never drawn, so every handle is left empty
+            .insert_resource(SpriteStorage {
+                workers: vec![Handle::default()],
+                ..default()
+            })
+            .add_systems(Startup, headless_setup)
+            .add_systems(Update, headless_next_day.run_if(in_state(DayCycleState::Night)))
+            .add_systems(Update, headless_bankrupt.run_if(in_state(DayCycleState::Bankrupt)))
+        ;
+    }
+}
+
+pub fn headless_setup(
+    mut commands: Commands,
+    sprites: Res<SpriteStorage>,
+    settings: Res<HeadlessSettings>,
+    mut ev_load: EventWriter<LoadGameEvent>,
+    mut next_day_state: ResMut<NextState<DayCycleState>>,
+    mut next_tutorial_state: ResMut<NextState<TutorialState>>,
+    game_rng: Res<GameRng>,
+) {
+    spawn_factory(&mut commands, &sprites);
+    println!("Seed: {}", game_rng.get_seed());
+
+    if let Some(path) = &settings.load {
+        ev_load.send(LoadGameEvent { path: path.clone() });
+    }
+    next_tutorial_state.set(TutorialState::Disabled);
+    next_day_state.set(DayCycleState::Day);
+}
+
+pub fn headless_next_day(
+    day_timer: Res<DayTimer>,
+    settings: Res<HeadlessSettings>,
+    money: Res<PlayerMoney>,
+    sold_items: Res<SoldItems>,
+    unsold_items: Res<UnsoldItems>,
+    mut import_selections: ResMut<ImportSelections>,
+    mut next_day_state: ResMut<NextState<DayCycleState>>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    let revenue: f32 = sold_items.items.iter().map(|(_, price)| price).sum();
+    println!(
+        "Day {}: money {:.2}, sold {} for {:.2}, unsold {}",
+        day_timer.day_count,
+        money.amount,
+        sold_items.items.len(),
+        revenue,
+        unsold_items.items.len()
+    );
+
+    if day_timer.day_count >= settings.days {
+        ev_exit.send(AppExit);
+        return;
+    }
+    import_selections.selected = settings.imports.clone();
+    next_day_state.set(DayCycleState::Day);
+}
+
+pub fn headless_bankrupt(
+    day_timer: Res<DayTimer>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    println!("Bankrupt on day {}", day_timer.day_count);
+    ev_exit.send(AppExit);
+}
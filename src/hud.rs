@@ -1,3 +1,4 @@
+use bevy::utils::HashMap;
 use kayak_ui::prelude::kayak_font::{TextLayout, TextProperties};
 
 use crate::*;
@@ -59,12 +60,40 @@ pub fn hud_container_render(
                         >
                             <PlayerMoneyHUDBundle/>
                             <ClockHUDBundle/>
+                            <SeedHUDBundle/>
+                            <ContractsHUDBundle/>
                             <PowerMinigameHUDBundle
                                 styles={KStyle {
                                     top: Units::Stretch(40.0).into(),
                                     ..default()
                                 }}
                             />
+                            <AssemblyRecipesHUDBundle
+                                styles={KStyle {
+                                    position_type: KPositionType::SelfDirected.into(),
+                                    width: Units::Pixels(320.0).into(),
+                                    height: Units::Pixels(200.0).into(),
+                                    top: Units::Stretch(0.65).into(),
+                                    bottom: Units::Stretch(1.0).into(),
+                                    left: Units::Stretch(1.0).into(),
+                                    right: Units::Pixels(25.0).into(),
+                                    z_index: StyleProp::Value(100).into(),
+                                    ..default()
+                                }}
+                            />
+                            <WorkerCandidatesHUDBundle
+                                styles={KStyle {
+                                    position_type: KPositionType::SelfDirected.into(),
+                                    width: Units::Pixels(320.0).into(),
+                                    height: Units::Pixels(220.0).into(),
+                                    top: Units::Stretch(0.65).into(),
+                                    bottom: Units::Stretch(1.0).into(),
+                                    left: Units::Stretch(1.0).into(),
+                                    right: Units::Pixels(25.0).into(),
+                                    z_index: StyleProp::Value(100).into(),
+                                    ..default()
+                                }}
+                            />
                             <AssembliesHudBundle
                                 props={AssembliesHudProps {
                                     image: base_hud_menu_image.clone(),
@@ -281,6 +310,147 @@ pub fn clock_hud_render(
     true
 }
 
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct SeedHUDProps;
+impl Widget for SeedHUDProps {}
+
+#[derive(Bundle)]
+pub struct SeedHUDBundle {
+    pub props: SeedHUDProps,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for SeedHUDBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                font_size: StyleProp::Value(24.0),
+                left: StyleProp::Value(Units::Stretch(1.0)),
+                top: StyleProp::Value(Units::Pixels(50.0)),
+                bottom: StyleProp::Value(Units::Stretch(1.0)),
+                position_type: StyleProp::Value(KPositionType::SelfDirected),
+                ..default()
+            },
+            computed_styles: Default::default(),
+            widget_name: SeedHUDProps::default().get_name(),
+        }
+    }
+}
+
+// Shown so a reported run can be replayed with --seed
+pub fn seed_hud_render(
+    In(entity): In<Entity>,
+    mut query: Query<(&mut SeedHUDProps, &mut ComputedStyles, &KStyle)>,
+    game_rng: Res<GameRng>,
+) -> bool {
+    if let Ok((_, mut computed_styles, style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            color: Color::BLACK.into(),
+            render_command: StyleProp::Value(RenderCommand::Text {
+                content: format!("Seed: {}", game_rng.get_seed()),
+                alignment: Alignment::Start,
+                word_wrap: false,
+                subpixel: false,
+                text_layout: TextLayout::default(),
+                properties: TextProperties::default()
+            }),
+            ..Default::default()
+        }
+        .with_style(style)
+        .into();
+    }
+    true
+}
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct ContractsHUDProps;
+impl Widget for ContractsHUDProps {}
+
+#[derive(Bundle)]
+pub struct ContractsHUDBundle {
+    pub props: ContractsHUDProps,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for ContractsHUDBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                left: StyleProp::Value(Units::Stretch(1.0)),
+                right: StyleProp::Value(Units::Pixels(25.0)),
+                top: StyleProp::Value(Units::Pixels(80.0)),
+                bottom: StyleProp::Value(Units::Stretch(1.0)),
+                width: StyleProp::Value(Units::Pixels(320.0)),
+                position_type: StyleProp::Value(KPositionType::SelfDirected),
+                ..default()
+            },
+            computed_styles: Default::default(),
+            widget_name: ContractsHUDProps::default().get_name(),
+        }
+    }
+}
+
+// Items waiting in export depots are counted so progress shows before they are delivered at night
+pub fn contracts_hud_render(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    widget_context: Res<KayakWidgetContext>,
+    mut query: Query<(&mut ContractsHUDProps, &mut ComputedStyles, &KStyle)>,
+    contracts: Res<Contracts>,
+    q_depot: Query<&ItemContainer, With<ItemExport>>,
+    q_items: Query<&Item>,
+) -> bool {
+    if let Ok((_, mut computed_styles, style)) = query.get_mut(entity) {
+        *computed_styles = KStyle::default()
+            .with_style(style)
+            .into();
+        let parent_id = Some(entity);
+
+        let mut waiting: HashMap<PurchasableItem, usize> = HashMap::new();
+        for item in q_depot.iter().flat_map(|container| container.items.iter().flatten()) {
+            if let Some(purchasable) = q_items.get(*item).ok().and_then(PurchasableItem::from_item) {
+                *waiting.entry(purchasable).or_insert(0) += 1;
+            }
+        }
+
+        rsx!(
+            <ElementBundle>
+                {
+                    for contract in contracts.active.iter() {
+                        // Earlier contracts for the same item take the waiting items first
+                        let waiting_count = waiting.entry(contract.item).or_insert(0);
+                        let ready = (*waiting_count).min(contract.get_remaining());
+                        *waiting_count -= ready;
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: format!(
+                                        "{} {}/{} by day {}",
+                                        contract.item.get_name(),
+                                        contract.delivered + ready,
+                                        contract.amount,
+                                        contract.due_day
+                                    ),
+                                    ..Default::default()
+                                }}
+                                styles={KStyle {
+                                    color: Color::BLACK.into(),
+                                    font_size: StyleProp::<f32>::Value(24.0),
+                                    ..Default::default()
+                                }}
+                            />
+                        );
+                    }
+                }
+            </ElementBundle>
+        );
+    }
+    true
+}
 
 #[derive(Component, Clone, PartialEq, Default)]
 pub struct PowerMinigameHUDProps;
@@ -367,6 +537,134 @@ pub fn power_minigame_hud_render(
     true
 }
 
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct AssemblyRecipesHUDProps;
+impl Widget for AssemblyRecipesHUDProps {}
+
+#[derive(Bundle)]
+pub struct AssemblyRecipesHUDBundle {
+    pub props: AssemblyRecipesHUDProps,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for AssemblyRecipesHUDBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..default()
+            },
+            computed_styles: Default::default(),
+            widget_name: AssemblyRecipesHUDProps::default().get_name(),
+        }
+    }
+}
+
+// Recipe selection for the assembly clicked into power mode
+pub fn assembly_recipes_hud_render(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    widget_context: Res<KayakWidgetContext>,
+    mut query: Query<(&mut AssemblyRecipesHUDProps, &mut ComputedStyles, &KStyle)>,
+    player_state: Res<State<PlayerState>>,
+    power_selection: Res<AssemblyPowerSelection>,
+    q_recipes: Query<&AssemblyRecipes>,
+) -> bool {
+    if let Ok((_, mut computed_styles, style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(style)
+        .into();
+        if player_state.get() == &PlayerState::Power {
+            let parent_id = Some(entity);
+
+            let Some(assembly) = power_selection.selected else { return true };
+            let Ok(recipes) = q_recipes.get(assembly) else { return true };
+            if recipes.recipes.len() < 2 { return true };
+
+            let background = assets.load("Worker Menu.png");
+            rsx!(
+                <NinePatchBundle
+                    nine_patch={NinePatch {
+                        handle: background,
+                        ..default()
+                    }}
+                    on_event={
+                        OnEvent::new(
+                            move |In(_entity): In<Entity>, event: ResMut<KEvent>, mut placement_state: ResMut<NextState<PlacementState>> | {
+                                if let EventType::Hover(_) = event.event_type {
+                                    placement_state.set(PlacementState::Blocked);
+                                }
+                                if let EventType::MouseOut(_) = event.event_type {
+                                    placement_state.set(PlacementState::Allowed);
+                                }
+                            }
+                        )
+                    }
+                >
+                    <TextWidgetBundle
+                        text={TextProps {
+                            content: "Recipes".to_string(),
+                            ..default()
+                        }}
+                        styles={KStyle {
+                            top: Units::Pixels(15.0).into(),
+                            left: Units::Stretch(1.0).into(),
+                            right: Units::Stretch(1.0).into(),
+                            ..default()
+                        }}
+                    />
+                    {
+                        for (i, recipe) in recipes.recipes.iter().enumerate() {
+                            let color = if i == recipes.selected { Color::rgb(0.1, 0.5, 0.1) } else { Color::rgb(0.0, 0.0, 0.0) };
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        height: Units::Pixels(28.0).into(),
+                                        top: Units::Pixels(10.0).into(),
+                                        ..default()
+                                    }}
+                                    on_event={OnEvent::new(
+                                        move |
+                                            In(_entity): In<Entity>,
+                                            event: ResMut<KEvent>,
+                                            mut ev_select_recipe: EventWriter<SelectRecipeEvent>,
+                                        | {
+                                            if let EventType::Click(_) = event.event_type {
+                                                ev_select_recipe.send(SelectRecipeEvent {
+                                                    assembly,
+                                                    recipe: i
+                                                });
+                                            }
+                                        }
+                                    )}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: recipe.get_description(),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            color: color.into(),
+                                            font_size: StyleProp::Value(20.0),
+                                            left: Units::Pixels(15.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            );
+                        }
+                    }
+                </NinePatchBundle>
+            );
+        }
+    }
+    true
+}
+
 pub fn widget_update_with_player_state<
 Props: PartialEq + Component + Clone,
 KState: PartialEq + Component + Clone,
@@ -622,6 +920,7 @@ pub fn assemblies_hud_render(
     assets: Res<AssetServer>,
     player_state: Res<State<PlayerState>>,
     assembly_prices: Res<AssemblyPrices>,
+    assembly_definitions: Res<AssemblyDefinitions>,
 ) -> bool {
     if let Ok((mut props, mut computed_styles, base_style, base_children, base_on_event)) = query.get_mut(entity) {
         *computed_styles = KStyle {
@@ -833,6 +1132,57 @@ pub fn assemblies_hud_render(
                         ..Default::default()
                     }}
                 />
+                {
+                    for (definition, icons) in assembly_definitions.definitions.iter().zip(assembly_definitions.icons.iter()) {
+                        let assembly_type = AssemblyType::Custom(definition.id);
+                        let button_click = OnEvent::new(
+                            move |In(_entity): In<Entity>, event: ResMut<KEvent>, mut selected_assembly: ResMut<SelectedAssembly> | {
+                                if let EventType::Click(_) = event.event_type {
+                                    selected_assembly.selected = assembly_type;
+                                }
+                            },
+                        );
+                        constructor!(
+                            <ImageButtonBundle
+                                props={ImageButtonProps {
+                                    image: icons.icon.clone(),
+                                    hover_image: icons.hover.clone(),
+                                    selected_image: icons.selected.clone(),
+                                    ..default()
+                                }}
+                                styles={KStyle {
+                                    width: Units::Pixels(128.0).into(),
+                                    height: Units::Pixels(64.0).into(),
+                                    offset: Edge::new(
+                                        Units::Stretch(1.0),
+                                        Units::Pixels(0.0),
+                                        Units::Stretch(1.0),
+                                        Units::Pixels(125.0),
+                                    ).into(),
+                                    ..default()
+                                }}
+                                on_event={
+                                    button_click
+                                }
+                            />
+                        );
+                        constructor!(
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: format!("${:.2}", assembly_prices.prices.get(&assembly_type).unwrap_or(&0.0)),
+                                    ..default()
+                                }}
+                                styles={KStyle {
+                                    width: Units::Stretch(0.0).into(),
+                                    left: Units::Pixels(-100.0).into(),
+                                    top: Units::Pixels(4.0).into(),
+                                    font_size: StyleProp::Value(21.0),
+                                    ..Default::default()
+                                }}
+                            />
+                        );
+                    }
+                }
             </NinePatchBundle>
             );
         } else {
@@ -142,7 +142,7 @@ pub struct ContainerInputSelectorBundle {
 }
 
 impl ContainerInputSelectorBundle {
-    pub fn new(asset_server: AssetServer) -> Self {
+    pub fn new(sprites: &SpriteStorage) -> Self {
         ContainerInputSelectorBundle {
             marker: ContainerInputSelector,
             sprite: SpriteBundle {
@@ -154,7 +154,7 @@ impl ContainerInputSelectorBundle {
                     custom_size: Some(Vec2::new(32.0, 64.0)),
                     ..Default::default()
                 },
-                texture: asset_server.load("Input Arrow.png"),
+                texture: sprites.input_arrow.clone(),
                 visibility: Visibility::Hidden,
                 ..Default::default()
             },
@@ -169,7 +169,7 @@ pub struct ContainerOutputSelectorBundle {
 }
 
 impl ContainerOutputSelectorBundle {
-    pub fn new(asset_server: AssetServer) -> Self {
+    pub fn new(sprites: &SpriteStorage) -> Self {
         ContainerOutputSelectorBundle {
             marker: ContainerOutputSelector,
             sprite: SpriteBundle {
@@ -182,7 +182,7 @@ impl ContainerOutputSelectorBundle {
                     custom_size: Some(Vec2::new(32.0, 64.0)),
                     ..Default::default()
                 },
-                texture: asset_server.load("Output Arrow.png"),
+                texture: sprites.output_arrow.clone(),
                 visibility: Visibility::Hidden,
                 ..Default::default()
             },
@@ -0,0 +1,315 @@
+use bevy::utils::HashMap;
+
+use crate::*;
+
+#[derive(Resource, Reflect)]
+pub struct ConveyorSettings {
+    pub price: f32,
+    // Tiles an item travels per second
+    pub speed: f32,
+}
+impl Default for ConveyorSettings {
+    fn default() -> Self {
+        Self {
+            price: 15.0,
+            speed: 1.5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum ConveyorDirection {
+    #[default]
+    Up,
+    Right,
+    Down,
+    Left
+}
+impl ConveyorDirection {
+    pub fn offset(self) -> IVec2 {
+        match self {
+            ConveyorDirection::Up => IVec2::new(0, 1),
+            ConveyorDirection::Right => IVec2::new(1, 0),
+            ConveyorDirection::Down => IVec2::new(0, -1),
+            ConveyorDirection::Left => IVec2::new(-1, 0),
+        }
+    }
+
+    // Conveyor sprites point up by default
+    pub fn rotation(self) -> Quat {
+        match self {
+            ConveyorDirection::Up => Quat::IDENTITY,
+            ConveyorDirection::Right => Quat::from_rotation_z(-std::f32::consts::PI / 2.0),
+            ConveyorDirection::Down => Quat::from_rotation_z(std::f32::consts::PI),
+            ConveyorDirection::Left => Quat::from_rotation_z(std::f32::consts::PI / 2.0),
+        }
+    }
+
+    pub fn rotate_clockwise(self) -> Self {
+        match self {
+            ConveyorDirection::Up => ConveyorDirection::Right,
+            ConveyorDirection::Right => ConveyorDirection::Down,
+            ConveyorDirection::Down => ConveyorDirection::Left,
+            ConveyorDirection::Left => ConveyorDirection::Up,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ConveyorPlacement {
+    pub direction: ConveyorDirection
+}
+
+// Carries one item at a time from the tile behind it to the tile in front of it
+#[derive(Component, Debug)]
+pub struct Conveyor {
+    pub direction: ConveyorDirection,
+    pub speed: f32,
+    pub item: Option<Entity>,
+    // How far the item is across this tile, from 0 to 1
+    pub progress: f32,
+}
+impl Clickable for Conveyor {}
+
+#[derive(Bundle)]
+pub struct ConveyorBundle {
+    pub conveyor: Conveyor,
+    pub solid: SolidEntity,
+    pub tile_size: EntityTileSize,
+    pub sprite: SpriteBundle,
+}
+impl GetGhostBundle for ConveyorBundle {
+    fn get_sprite_bundle(&self) -> Option<SpriteBundle> {
+        Some(self.sprite.clone())
+    }
+    fn get_tile_size(&self) -> Option<EntityTileSize> {
+        Some(self.tile_size)
+    }
+}
+impl DefaultWithSprites for ConveyorBundle {
+    fn default_with_sprites(sprites: &SpriteStorage) -> Self {
+        ConveyorBundle {
+            conveyor: Conveyor {
+                direction: ConveyorDirection::Up,
+                speed: ConveyorSettings::default().speed,
+                item: None,
+                progress: 0.0,
+            },
+            solid: SolidEntity,
+            tile_size: EntityTileSize(IVec2::new(1, 1)),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: Color::GRAY,
+                    custom_size: Some(Vec2::new(TILE_SIZE.x, TILE_SIZE.y)),
+                    ..default()
+                },
+                texture: sprites.output_arrow.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..default()
+            },
+        }
+    }
+}
+
+pub fn spawn_conveyor(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    pos: Vec2,
+    direction: ConveyorDirection,
+    speed: f32,
+    item: Option<Entity>,
+) -> Entity {
+    let mut bundle = ConveyorBundle::default_with_sprites(sprites);
+    bundle.conveyor.direction = direction;
+    bundle.conveyor.speed = speed;
+    bundle.conveyor.item = item;
+    bundle.sprite.transform.translation = Vec3::new(pos.x, pos.y, bundle.sprite.transform.translation.z);
+    bundle.sprite.transform.rotation = direction.rotation();
+    commands.spawn(bundle).id()
+}
+
+pub fn input_toggle_conveyor_mode(
+    input: Res<Input<KeyCode>>,
+    state: Res<State<PlayerState>>,
+    mut next_state: ResMut<NextState<PlayerState>>
+) {
+    if input.just_pressed(KeyCode::C) {
+        if state.get() == &PlayerState::Conveyors {
+            next_state.set(PlayerState::None);
+        } else {
+            next_state.set(PlayerState::Conveyors);
+        }
+    }
+}
+
+pub fn rotate_conveyor_placement(
+    input: Res<Input<KeyCode>>,
+    mut placement: ResMut<ConveyorPlacement>,
+    mut q_ghost: Query<&mut Transform, With<HoverGhost>>,
+) {
+    if input.just_pressed(KeyCode::R) {
+        placement.direction = placement.direction.rotate_clockwise();
+    }
+    for mut transform in q_ghost.iter_mut() {
+        transform.rotation = placement.direction.rotation();
+    }
+}
+
+pub fn place_conveyor(
+    mut commands: Commands,
+    input: Res<Input<MouseButton>>,
+    mouse_tile: Res<MouseTile>,
+    settings: Res<ConveyorSettings>,
+    placement: Res<ConveyorPlacement>,
+    mut money: ResMut<PlayerMoney>,
+    collision_grid: Res<CollisionGrid>,
+    tilemap_q: Query<(&TilemapGridSize, &TilemapType, &Transform)>,
+    sprites: Res<SpriteStorage>,
+) {
+    if input.just_pressed(MouseButton::Left) {
+        if collision_grid.is_solid(&mouse_tile.0) {
+            println!("Can't place conveyor here");
+            return;
+        }
+        let Ok(_) = money.try_remove_money(settings.price) else {
+            println!("Not enough money to place conveyor");
+            return
+        };
+
+        let (grid_size, map_type, map_transform) = tilemap_q.single();
+        let pos = get_tile_world_pos(&mouse_tile.0, map_transform, grid_size, map_type);
+        spawn_conveyor(&mut commands, &sprites, pos, placement.direction, settings.speed, None);
+    }
+}
+
+pub fn refund_conveyor(
+    mut commands: Commands,
+    mut ev_conveyor_mouse: EventReader<GenericMouseCollisionEvent<Conveyor>>,
+    input: Res<Input<KeyCode>>,
+    settings: Res<ConveyorSettings>,
+    mut money: ResMut<PlayerMoney>,
+) {
+    if input.just_pressed(KeyCode::Delete) {
+        let Some(ev) = ev_conveyor_mouse.read().next() else { return };
+        let Some((_, conveyor)) = ev.collision else { return };
+        money.add_money(settings.price);
+        commands.entity(conveyor).despawn_recursive();
+    }
+}
+
+fn get_neighbor_tile(tile: TilePos, offset: IVec2, map_size: &TilemapSize) -> Option<TilePos> {
+    let x = tile.x as i32 + offset.x;
+    let y = tile.y as i32 + offset.y;
+    if x < 0 || y < 0 || x >= map_size.x as i32 || y >= map_size.y as i32 {
+        return None;
+    }
+    Some(TilePos { x: x as u32, y: y as u32 })
+}
+
+pub fn move_conveyor_items(
+    mut commands: Commands,
+    mut q_conveyors: Query<(Entity, &mut Conveyor, &Transform)>,
+    // Export depots aren't solid but should still accept items
+    mut q_containers: Query<
+        (Entity, &Transform, Option<&EntityTileSize>, &Sprite, Option<&mut ItemContainer>, Option<&mut ItemIOContainer>),
+        (Or<(With<SolidEntity>, With<ItemExport>)>, Without<Conveyor>, Without<Item>)
+    >,
+    mut q_items: Query<(&Item, &mut Transform), Without<Conveyor>>,
+    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &Transform), (Without<SolidEntity>, Without<Item>)>,
+    locked_items: Res<ItemJobLock>,
+    time: Res<Time>,
+) {
+    let Ok((map_size, grid_size, map_type, map_transform)) = q_tilemap.get_single() else { return };
+
+    let mut conveyor_tiles = HashMap::new();
+    for (entity, _, transform) in q_conveyors.iter() {
+        let Some(tile) = get_entity_tiles(transform, None, map_size, grid_size, map_type, map_transform).first().copied() else { continue };
+        conveyor_tiles.insert(tile, entity);
+    }
+    let mut container_tiles = HashMap::new();
+    for (entity, transform, tile_size, sprite, container, io_container) in q_containers.iter() {
+        if container.is_none() && io_container.is_none() { continue }
+        let sprite_size = sprite.custom_size.unwrap_or(Vec2::new(TILE_SIZE.x, TILE_SIZE.y));
+        let sprite_tile_size = EntityTileSize(IVec2::new((sprite_size.x / TILE_SIZE.x) as i32, (sprite_size.y / TILE_SIZE.y) as i32));
+        let tile_size = tile_size.unwrap_or(&sprite_tile_size);
+        for tile in get_entity_tiles(transform, Some(tile_size), map_size, grid_size, map_type, map_transform) {
+            container_tiles.insert(tile, entity);
+        }
+    }
+
+    // Belts hand items on in tile order so headless runs play out the same every time
+    let mut sorted_conveyors = conveyor_tiles.iter().map(|(tile, entity)| (*tile, *entity)).collect::<Vec<_>>();
+    sorted_conveyors.sort_by_key(|(tile, _)| (tile.y, tile.x));
+
+    for (tile, conveyor_entity) in sorted_conveyors.iter() {
+        let Ok((_, mut conveyor, _)) = q_conveyors.get_mut(*conveyor_entity) else { continue };
+        let direction = conveyor.direction;
+
+        let Some(item) = conveyor.item else {
+            // Pull from whatever container is behind the conveyor
+            let Some(source) = get_neighbor_tile(*tile, -direction.offset(), map_size)
+                .and_then(|behind| container_tiles.get(&behind)) else { continue };
+            let Ok((_, _, _, _, container, io_container)) = q_containers.get_mut(*source) else { continue };
+            let source_container = match (io_container, container) {
+                (Some(io_container), _) => &mut io_container.into_inner().output,
+                (None, Some(container)) => container.into_inner(),
+                _ => continue
+            };
+            // Items a worker is already on the way to pick up stay put
+            let Some(index) = source_container.items.iter()
+                .rposition(|item| item.is_some_and(|item| !locked_items.items.contains(&item))) else { continue };
+            let Ok(Some(item)) = source_container.remove_index(index) else { continue };
+
+            commands.entity(*source).remove_children(&[item]);
+            commands.entity(*conveyor_entity).add_child(item);
+            conveyor.item = Some(item);
+            conveyor.progress = 0.0;
+            continue;
+        };
+
+        conveyor.progress = (conveyor.progress + conveyor.speed * time.delta_seconds()).min(1.0);
+        let progress = conveyor.progress;
+        let Ok((item_type, mut item_transform)) = q_items.get_mut(item) else {
+            // Item was taken off the belt
+            conveyor.item = None;
+            continue;
+        };
+        let item_type = *item_type;
+        // Items are children of the rotated conveyor, so move along local y and undo the rotation
+        *item_transform = Transform::from_xyz(0.0, (progress - 0.5) * TILE_SIZE.y, 1.0)
+            .with_rotation(direction.rotation().inverse());
+        if progress < 1.0 { continue }
+
+        let Some(next_tile) = get_neighbor_tile(*tile, direction.offset(), map_size) else { continue };
+        if let Some(next_conveyor_entity) = conveyor_tiles.get(&next_tile) {
+            let Ok((_, mut next_conveyor, _)) = q_conveyors.get_mut(*next_conveyor_entity) else { continue };
+            if next_conveyor.item.is_some() { continue }
+            next_conveyor.item = Some(item);
+            next_conveyor.progress = 0.0;
+            commands.entity(*conveyor_entity).remove_children(&[item]);
+            commands.entity(*next_conveyor_entity).add_child(item);
+        } else if let Some(target) = container_tiles.get(&next_tile) {
+            let Ok((_, _, _, _, container, io_container)) = q_containers.get_mut(*target) else { continue };
+            let target_container = match (io_container, container) {
+                (Some(io_container), _) => &mut io_container.into_inner().input,
+                (None, Some(container)) => container.into_inner(),
+                _ => continue
+            };
+            // add_item checks max_items and item_type, the item waits at the end of the belt until it fits
+            let transform = target_container.get_transform();
+            if target_container.add_item((Some(item), Some(item_type))).is_err() { continue }
+
+            if let Ok((_, mut item_transform)) = q_items.get_mut(item) {
+                *item_transform = transform;
+            }
+            commands.entity(*conveyor_entity).remove_children(&[item]);
+            commands.entity(*target).add_child(item);
+        } else {
+            continue;
+        }
+
+        let Ok((_, mut conveyor, _)) = q_conveyors.get_mut(*conveyor_entity) else { continue };
+        conveyor.item = None;
+        conveyor.progress = 0.0;
+    }
+}
@@ -1,11 +1,125 @@
+use bevy::utils::HashMap;
+
 use crate::*;
 
+// Choices the Max button cycles through, None sells without a limit
+const SELL_LIMIT_OPTIONS: [Option<usize>; 6] = [None, Some(1), Some(5), Some(10), Some(20), Some(40)];
+const MIN_PRICE_STEP: f32 = 0.5;
+
 #[derive(Component, Debug, Reflect)]
 pub struct ItemExport;
 
+// How an export depot sells one item type at night
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SellRule {
+    pub min_price: f32,
+    pub max_per_night: Option<usize>,
+    // Keep everything in the depot, e.g. for a contract or a better price later
+    pub hold: bool,
+}
+impl Default for SellRule {
+    fn default() -> Self {
+        Self {
+            min_price: 0.0,
+            max_per_night: None,
+            hold: false,
+        }
+    }
+}
+impl SellRule {
+    pub fn get_hold_reason(&self, price: f32, sold_tonight: usize) -> Option<HoldReason> {
+        if self.hold {
+            return Some(HoldReason::Hold);
+        }
+        if price < self.min_price {
+            return Some(HoldReason::BelowMinPrice);
+        }
+        if self.max_per_night.is_some_and(|max| sold_tonight >= max) {
+            return Some(HoldReason::NightLimit);
+        }
+        None
+    }
+
+    pub fn get_description(&self) -> String {
+        if self.hold {
+            return "Hold".to_string();
+        }
+        let max = match self.max_per_night {
+            Some(max) => format!("max {}/night", max),
+            None => "no limit".to_string(),
+        };
+        format!("Min ${:.2}, {}", self.min_price, max)
+    }
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct ExportSellRules {
+    pub rules: HashMap<PurchasableItem, SellRule>
+}
+impl ExportSellRules {
+    pub fn get(&self, item: &PurchasableItem) -> SellRule {
+        self.rules.get(item).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HoldReason {
+    Hold,
+    BelowMinPrice,
+    NightLimit,
+}
+impl HoldReason {
+    pub fn get_name(&self) -> &str {
+        match self {
+            HoldReason::Hold => "held",
+            HoldReason::BelowMinPrice => "below min price",
+            HoldReason::NightLimit => "over nightly limit",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SellRuleButton {
+    MinDown,
+    MinUp,
+    Max,
+    Hold,
+}
+impl SellRuleButton {
+    pub const ALL: [SellRuleButton; 4] = [
+        SellRuleButton::MinDown,
+        SellRuleButton::MinUp,
+        SellRuleButton::Max,
+        SellRuleButton::Hold,
+    ];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            SellRuleButton::MinDown => "-$",
+            SellRuleButton::MinUp => "+$",
+            SellRuleButton::Max => "Max",
+            SellRuleButton::Hold => "Hold",
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct EditSellRuleEvent {
+    pub depot: Entity,
+    pub item: PurchasableItem,
+    pub button: SellRuleButton,
+}
+
+// Export depot whose rules are shown on the night screen
+#[derive(Resource, Default)]
+pub struct SelectedExportDepot {
+    pub index: usize
+}
+
 #[derive(Bundle)]
 pub struct ItemExportBundle {
     pub depot: ItemExport,
+    pub rules: ExportSellRules,
     pub sprite: SpriteBundle,
     pub items: ItemContainer
 }
@@ -35,10 +149,17 @@ pub struct UnsoldItems {
     pub items: Vec<(Item, f32)>
 }
 
+// Items kept back by sell rules, they stay in the depot
+#[derive(Resource, Default)]
+pub struct HeldItems {
+    pub items: Vec<(Item, f32, HoldReason)>
+}
+
 impl DefaultWithSprites for ItemExportBundle {
     fn default_with_sprites(sprites: &SpriteStorage) -> Self {
         ItemExportBundle {
             depot: ItemExport,
+            rules: ExportSellRules::default(),
             items: ItemContainer {
                 items: Vec::new(),
                 item_type: None,
@@ -59,37 +180,55 @@ impl DefaultWithSprites for ItemExportBundle {
     }
 }
 
+pub fn spawn_item_export(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    item_exports: ItemExportBundle,
+) -> Entity {
+    let mut input_bundle = ContainerInputSelectorBundle::new(sprites);
+    input_bundle.sprite.transform.translation = Vec3::new(0.0, 42.0, 1.0);
+    input_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI);
+    let input_entity = commands.spawn(input_bundle).id();
+
+    let mut export_commands = commands.spawn(item_exports);
+    export_commands.push_children(&[input_entity]);
+    export_commands.id()
+}
+
 pub fn sell_export_items(
     mut commands: Commands,
     mut economy: ResMut<Economy>,
     mut money: ResMut<PlayerMoney>,
     mut q_items: Query<&mut Item>,
-    mut q_depot: Query<(&ItemExport, &mut ItemContainer)>,
+    mut q_depot: Query<(&ExportSellRules, &mut ItemContainer), With<ItemExport>>,
     mut sold_items: ResMut<SoldItems>,
-    mut unsold_items: ResMut<UnsoldItems>
+    mut unsold_items: ResMut<UnsoldItems>,
+    mut held_items: ResMut<HeldItems>,
 ) {
-    for (export, mut container) in q_depot.iter_mut() {
+    for (rules, container) in q_depot.iter_mut() {
         let mut container_ref = container;
-        let mut sold = 0;
+        let mut sold_counts: HashMap<PurchasableItem, usize> = HashMap::new();
         container_ref.items.retain(|item_entity| {
             let Some(item_entity) = item_entity else { return true; };
             let Ok(mut item) = q_items.get_mut(*item_entity) else { return true; };
             let Some(price) = item.get_price(&economy) else { return true; };
+            let Some(purchasable) = PurchasableItem::from_item(&item) else { return true; };
 
-            let Some(demand) = item.get_demand(&economy) else { return true; };
-            if demand <= sold as f32 { 
-                unsold_items.items.push((item.clone(), price));
+            let sold = sold_counts.entry(purchasable).or_insert(0);
+            if let Some(reason) = rules.get(&purchasable).get_hold_reason(price, *sold) {
+                held_items.items.push((*item, price, reason));
                 return true;
             }
-            if let Err(e) = item.sell(&mut economy, 1) {
-                println!("{:?}", e);
+
+            // Each sale uses up demand, so the market stops buying once it is saturated
+            if item.sell(&mut economy, 1).is_err() {
                 unsold_items.items.push((item.clone(), price));
                 return true
             }
             sold_items.items.push((item.clone(), price));
             println!("Selling item: {:?}", item_entity);
             money.add_money(price);
-            sold += 1;
+            *sold += 1;
 
             commands.entity(*item_entity).insert(DespawnLater);
             return false;
@@ -118,7 +257,6 @@ pub fn place_item_export(
     input: Res<Input<MouseButton>>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    asset_server: Res<AssetServer>,
     sprites: Res<SpriteStorage>,
     tilemap_q: Query<(
         &TilemapSize,
@@ -135,7 +273,7 @@ pub fn place_item_export(
         let Some(tile_pos) = get_mouse_tile(window, camera, camera_transform, tilemap_size, grid_size, map_type, map_transform) else { return };
         let pos = get_tile_world_pos(&tile_pos, map_transform, grid_size, map_type);
 
-        let mut input_bundle = ContainerInputSelectorBundle::new(asset_server.clone());
+        let mut input_bundle = ContainerInputSelectorBundle::new(&sprites);
         input_bundle.sprite.transform.translation = Vec3::new(0.0, 42.0, 1.0);
         let input_entity = commands.spawn(input_bundle).id();
 
@@ -143,3 +281,22 @@ pub fn place_item_export(
             .push_children(&[input_entity]);
     }
 }
+
+pub fn edit_sell_rules(
+    mut ev_edit: EventReader<EditSellRuleEvent>,
+    mut q_rules: Query<&mut ExportSellRules>,
+) {
+    for ev in ev_edit.read() {
+        let Ok(mut rules) = q_rules.get_mut(ev.depot) else { continue };
+        let rule = rules.rules.entry(ev.item).or_default();
+        match ev.button {
+            SellRuleButton::MinDown => rule.min_price = (rule.min_price - MIN_PRICE_STEP).max(0.0),
+            SellRuleButton::MinUp => rule.min_price += MIN_PRICE_STEP,
+            SellRuleButton::Max => {
+                let i = SELL_LIMIT_OPTIONS.iter().position(|max| *max == rule.max_per_night).unwrap_or(0);
+                rule.max_per_night = SELL_LIMIT_OPTIONS[(i + 1) % SELL_LIMIT_OPTIONS.len()];
+            },
+            SellRuleButton::Hold => rule.hold = !rule.hold,
+        }
+    }
+}
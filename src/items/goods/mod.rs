@@ -4,7 +4,7 @@ mod good_types;
 use bevy::{ecs::system::EntityCommands, reflect::Enum};
 pub use good_types::*;
 
-#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy)]
+#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum GoodItem {
     Paper
 }
@@ -53,6 +53,21 @@ impl DefaultWithSprites for ItemImportBundle {
     }
 }
 
+pub fn spawn_item_import(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    item_imports: ItemImportBundle,
+) -> Entity {
+    let mut output_bundle = ContainerOutputSelectorBundle::new(sprites);
+    output_bundle.sprite.transform.translation = Vec3::new(0.0, -42.0, 1.0);
+    output_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI);
+    let output_entity = commands.spawn(output_bundle).id();
+
+    let mut import_commands = commands.spawn(item_imports);
+    import_commands.push_children(&[output_entity]);
+    import_commands.id()
+}
+
 pub fn purchase_item_imports(
     mut commands: Commands,
     mut q_imports: Query<(Entity, &mut ItemContainer), With<ItemImport>>,
@@ -114,7 +129,6 @@ pub fn place_item_import(
     input: Res<Input<MouseButton>>,
     q_window: Query<&Window, With<PrimaryWindow>>,
     q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    asset_server: Res<AssetServer>,
     sprites: Res<SpriteStorage>,
     tilemap_q: Query<(
         &TilemapSize,
@@ -133,7 +147,7 @@ pub fn place_item_import(
         let size = ItemImportBundle::default_with_sprites(&sprites).tile_size.0;
         let pos = get_corner_tile_pos(get_tile_world_pos(&tile_pos, map_transform, grid_size, map_type), size);
 
-        let mut output_bundle = ContainerOutputSelectorBundle::new(asset_server.clone());
+        let mut output_bundle = ContainerOutputSelectorBundle::new(&sprites);
         output_bundle.sprite.transform.translation = Vec3::new(0.0, -42.0, 1.0);
         let output_entity = commands.spawn(output_bundle).id();
 
@@ -2,7 +2,7 @@ use bevy::{ecs::system::EntityCommands, reflect::Enum};
 
 use crate::*;
 
-#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Reflect, Hash)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Reflect, Hash, Serialize, Deserialize)]
 pub enum MaterialItem {
     WoodPulp
 }
@@ -22,6 +22,9 @@ pub use exports::*;
 mod materials;
 pub use materials::*;
 
+mod conveyor;
+pub use conveyor::*;
+
 pub struct ItemPlugin;
 
 impl Plugin for ItemPlugin {
@@ -65,26 +68,68 @@ impl Plugin for ItemPlugin {
             // ).run_if(in_state(DayCycleState::Day)))
             .add_systems(Update, (move_container_items, add_container_items))
             .add_systems(OnEnter(DayCycleState::Night), (sell_export_items))
-            .add_systems(OnExit(DayCycleState::Night), (purchase_item_imports, |mut sold_items: ResMut<SoldItems>| sold_items.items.clear()))
+            .add_systems(OnExit(DayCycleState::Night), (purchase_item_imports, |mut sold_items: ResMut<SoldItems>, mut held_items: ResMut<HeldItems>| {
+                sold_items.items.clear();
+                held_items.items.clear();
+            }))
+            .add_systems(Update, edit_sell_rules.run_if(in_state(DayCycleState::Night)))
+            .add_event::<EditSellRuleEvent>()
             .insert_resource(SoldItems::default())
             .insert_resource(UnsoldItems::default())
+            .init_resource::<HeldItems>()
+            .init_resource::<SelectedExportDepot>()
             // .add_systems(Update, (
             //     place_export.run_if(in_state(PlayerState::Export)),
             //     input_toggle_export_mode
             // ).run_if(in_state(DayCycleState::Day)))
             .add_event::<GenericMouseCollisionEvent<Item>>()
+            .add_systems(OnEnter(PlayerState::Conveyors),
+                |mut ev_show_ghost: EventWriter<ShowHoverGhost<ConveyorBundle>>| {
+                    ev_show_ghost.send(ShowHoverGhost::<ConveyorBundle> {
+                        bundle: PhantomData::<ConveyorBundle>
+                    });
+                }
+            )
+            .add_systems(OnExit(PlayerState::Conveyors),
+                |mut ev_hide_ghost: EventWriter<HideHoverGhost>| {
+                    ev_hide_ghost.send(HideHoverGhost);
+                }
+            )
+            .add_systems(Update, show_hover_ghost::<ConveyorBundle>)
+            .add_event::<ShowHoverGhost::<ConveyorBundle>>()
+            .add_systems(Update, (
+                (place_conveyor).run_if(in_state(PlayerState::Conveyors)).run_if(in_state(PlacementState::Allowed)),
+                (rotate_conveyor_placement).run_if(in_state(PlayerState::Conveyors)),
+                input_toggle_conveyor_mode,
+                refund_conveyor,
+                move_conveyor_items,
+            ).run_if(in_state(DayCycleState::Day)))
+            .add_systems(PreUpdate, mouse_collision_system::<Conveyor>)
+            .add_event::<GenericMouseCollisionEvent<Conveyor>>()
+            .insert_resource(ConveyorSettings::default())
+            .insert_resource(ConveyorPlacement::default())
             .register_type::<ItemContainer>()
         ;
     }
 }
 
-#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy)]
+#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum Item {
     Good(GoodItem),
     Resource(ResourceItem),
     Material(MaterialItem)
 }
 
+impl Item {
+    pub const ALL: [Item; 5] = [
+        Item::Resource(ResourceItem::Wood),
+        Item::Resource(ResourceItem::WoodChips),
+        Item::Resource(ResourceItem::Lumber),
+        Item::Material(MaterialItem::WoodPulp),
+        Item::Good(GoodItem::Paper),
+    ];
+}
+
 impl ItemType for Item {
     fn get_name (&self) -> &str {
         match self {
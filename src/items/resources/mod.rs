@@ -5,7 +5,7 @@ use bevy::{ecs::system::EntityCommands, reflect::Enum};
 pub use resource_types::*;
 
 // TODO: Macro
-#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy)]
+#[derive(Component, PartialEq, Debug, Reflect, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum ResourceItem {
     Wood,
     WoodChips,
@@ -8,6 +8,7 @@ use pathfinding::prelude::astar;
 use bevy_inspector_egui::quick::{WorldInspectorPlugin, StateInspectorPlugin};
 use bevy_inspector_egui::quick::ResourceInspectorPlugin;
 use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
+use serde::{Deserialize, Serialize};
 
 use kayak_ui::{
     prelude::{widgets::*, *},
@@ -56,6 +57,18 @@ use tutorial::*;
 mod bankrupt;
 use bankrupt::*;
 
+mod headless;
+use headless::*;
+
+mod save;
+use save::*;
+
+mod floor;
+use floor::*;
+
+mod game_rng;
+use game_rng::*;
+
 const GRID_SIZE: TilemapSize = TilemapSize { x: 180, y: 180 };
 const TILE_SIZE: TilemapTileSize = TilemapTileSize { x: 16.0, y: 16.0 };
 
@@ -68,7 +81,10 @@ pub enum PlayerState {
     Jobs,
     Imports,
     Export,
-    Power
+    Power,
+    Conveyors,
+    Cables,
+    Floor
 }
 
 #[derive(States, PartialEq, Eq, Debug, Clone, Hash, Default, Reflect)]
@@ -79,16 +95,31 @@ pub enum PlacementState {
 }
 
 fn main() {
-    App::new()
-        .insert_resource(AssetMetaCheck::Never)
-        .add_plugins(EmbeddedAssetPlugin { mode: PluginMode::ReplaceDefault })
-        .add_plugins(DefaultPlugins)
-        .add_plugins(WindowResizePlugin)
-        // .add_plugins(WorldInspectorPlugin::default())
-        // .add_plugins(ResourceInspectorPlugin::<Economy>::default())
-        .add_plugins(TilemapPlugin)
-        .add_plugins((KayakContextPlugin, KayakWidgets))
+    let mut app = App::new();
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.iter().any(|arg| arg == "--headless") {
+        app.add_plugins(HeadlessPlugin::from_args(&args));
+    } else {
+        app
+            .insert_resource(AssetMetaCheck::Never)
+            .add_plugins(EmbeddedAssetPlugin { mode: PluginMode::ReplaceDefault })
+            .add_plugins(DefaultPlugins)
+            .add_plugins(WindowResizePlugin)
+            // .add_plugins(WorldInspectorPlugin::default())
+            // .add_plugins(ResourceInspectorPlugin::<Economy>::default())
+            .add_plugins(TilemapPlugin)
+            .add_plugins((KayakContextPlugin, KayakWidgets))
+
+            .add_systems(Startup, (factory_setup, apply_deferred, ui_setup).chain())
+            .add_systems(Update, (camera_follow, camera_scroll_zoom).run_if(in_state(DayCycleState::Day)))
+            .add_systems(Update, (hide_hover_ghost, hover_ghost_tracking))
+            .add_systems(PreUpdate, (set_mouse_pos_res, set_mouse_tile_res))
+            .add_systems(PreUpdate, block_keys_while_typing.after(bevy::input::InputSystem))
+            .insert_resource(SpriteStorage::default());
+    }
 
+    app
         .add_plugins(AssembliesPlugin)
         .add_plugins(WorkerPlugin)
         .add_plugins(ItemPlugin)
@@ -96,20 +127,16 @@ fn main() {
         .add_plugins(OpeningPlugin)
         .add_plugins(TutorialPlugin)
         .add_plugins(BankruptPlugin)
+        .add_plugins(SavePlugin)
+        .add_plugins(FloorPlugin)
+        .add_plugins(GameRngPlugin::from_args(&args))
 
         .add_systems(Update, day_timer_system.run_if(in_state(DayCycleState::Day)).run_if(in_state(TutorialState::Disabled)))
-        .add_systems(OnEnter(DayCycleState::Night), (
-            |mut day_timer: ResMut<DayTimer>| {
-                day_timer.day_timer.reset();
-                day_timer.day_count += 1;
-            },
-            reset_factory
-        ))
+        .add_systems(OnEnter(DayCycleState::Night), (advance_day, reset_factory))
         .add_state::<DayCycleState>()
         .insert_resource(DayTimer::default())
         .insert_resource(ImportSelections::default())
 
-        .add_systems(Startup, (factory_setup, apply_deferred, ui_setup).chain())
         .add_systems(FixedUpdate, (
             (player_movement).run_if(not(in_state(PlayerState::Power))),
             move_entities
@@ -125,76 +152,41 @@ fn main() {
             player_power_assembly,
             activate_power_mode_on_click
         ).run_if(in_state(DayCycleState::Day)))
-        .add_systems(Update, (camera_follow, camera_scroll_zoom).run_if(in_state(DayCycleState::Day)))
         .add_systems(PostUpdate, despawn_later_system)
         .add_systems(Update, input_reset_player_mode)
         .add_systems(Update, (sprite_direction_system, movement_animation_system))
         .insert_resource(AssemblyPowerSelection::default())
 
-        .add_systems(PostUpdate, (set_tilemap_collisions).run_if(on_timer(Duration::from_secs_f32(0.1))))
+        .add_systems(PostUpdate, update_collision_grid)
+        .add_systems(Update, update_path_congestion.run_if(on_timer(Duration::from_secs(1))))
+        .init_resource::<CollisionGrid>()
+        .init_resource::<PathCache>()
+        .init_resource::<PathCongestion>()
 
-        .add_systems(Update, (hide_hover_ghost, hover_ghost_tracking))
         .add_event::<HideHoverGhost>()
 
         .add_state::<PlayerState>()
         .add_state::<PlacementState>()
-        .add_systems(PreUpdate, (set_mouse_pos_res, set_mouse_tile_res))
         .insert_resource(MousePos(Vec2::ZERO))
         .insert_resource(MouseTile(TilePos::new(0, 0)))
-        .insert_resource(SpriteStorage::default())
         .run();
 }
 
 #[derive(Component, Debug)]
 pub struct Path (Vec<TilePos>);
 
-#[derive(Component)]
-pub struct TileMapCollision;
-
 #[derive(Component)]
 pub struct SolidEntity;
 
 #[derive(Component, Clone, Copy)]
 pub struct EntityTileSize (IVec2);
 
-// TODO: Change detection?
-pub fn set_tilemap_collisions (
-    mut commands: Commands,
-    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &TileStorage, &Transform)>,
-    q_collisions: Query<(Entity, &TilePos), With<TileMapCollision>>,
-    q_solid: Query<(&Transform, Option<&EntityTileSize>), With<SolidEntity>>,
-) {
-    for (entity, _) in q_collisions.iter() {
-        commands.entity(entity).remove::<TileMapCollision>();
-    }
-    for (transform, tile_size) in q_solid.iter() {
-        let default_size = &EntityTileSize(IVec2::new(1, 1));
-        let tile_size = tile_size.unwrap_or(default_size);
-        let (map_size, grid_size, map_type, tile_storage, map_transform) = q_tilemap.single();
-
-        let world_pos = get_world_pos(Vec2 { x: transform.translation.x, y: transform.translation.y }, map_transform)
-            - Vec2::new((((tile_size.0.x as f32) / 2.0) - 0.5) * TILE_SIZE.x, (((tile_size.0.y as f32) / 2.0) - 0.5) * TILE_SIZE.y);
-
-        // TODO: Rotation
-        let Some(tile_pos) = TilePos::from_world_pos(&world_pos, map_size, grid_size, map_type) else { continue };
-        let x = tile_size.0.x;
-        let y = tile_size.0.y;
-
-        for x in 0..x as u32 {
-            for y in 0..y as u32 {
-                let tile_pos = TilePos { x: tile_pos.x + x, y: tile_pos.y + y };
-                let Some(tile) = tile_storage.get(&tile_pos) else { continue };
-                commands.entity(tile).insert(TileMapCollision);
-            }
-        }
-    }
-}
-
 fn debug_collision(
-    mut q_collisions: Query<(&mut TileColor, Option<&TileMapCollision>)>,
+    mut q_tiles: Query<(&TilePos, &mut TileColor)>,
+    grid: Res<CollisionGrid>,
 ) {
-    for (mut color, collision) in q_collisions.iter_mut() {
-        color.0 = if collision.is_some() {Color::RED} else {Color::WHITE};
+    for (tile_pos, mut color) in q_tiles.iter_mut() {
+        color.0 = if grid.is_solid(tile_pos) {Color::RED} else {Color::WHITE};
     }
 }
 
@@ -222,6 +214,10 @@ pub struct SpriteStorage {
     pub wood_chipper: Handle<Image>,
     pub wood_chipper_hover: Handle<Image>,
     pub wood_chipper_selected: Handle<Image>,
+    pub player: Handle<TextureAtlas>,
+    pub tiles: Handle<Image>,
+    pub input_arrow: Handle<Image>,
+    pub output_arrow: Handle<Image>,
     pub imports: Handle<Image>,
     pub exports: Handle<Image>,
     pub wood: Handle<Image>,
@@ -268,10 +264,25 @@ pub fn factory_setup(
     sprites.imports = asset_server.load("Imports.png");
     sprites.exports = asset_server.load("Exports.png");
 
-    let texture_handle: Handle<Image> = asset_server.load("tiles_map.png");
+    sprites.input_arrow = asset_server.load("Input Arrow.png");
+    sprites.output_arrow = asset_server.load("Output Arrow.png");
+
+    sprites.tiles = asset_server.load("tiles_map.png");
+
+    let texture_handle = asset_server.load("Character placeholder.png");
+    let texture_atlas =
+        TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 64.0), 24, 5, None, None);
+    sprites.player = texture_atlases.add(texture_atlas);
 
     commands.spawn((Camera2dBundle::default(), MainCamera, CameraUIKayak));
 
+    spawn_factory(&mut commands, &sprites);
+}
+
+pub fn spawn_factory(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+) {
     let tilemap_entity = commands.spawn_empty().id();
     let mut tile_storage = TileStorage::empty(GRID_SIZE);
 
@@ -308,24 +319,19 @@ pub fn factory_setup(
         map_type,
         size: GRID_SIZE,
         storage: tile_storage,
-        texture: TilemapTexture::Single(texture_handle),
+        texture: TilemapTexture::Single(sprites.tiles.clone()),
         tile_size: TILE_SIZE,
         transform: get_tilemap_center_transform(&GRID_SIZE, &grid_size, &map_type, -100.0),
         ..Default::default()
     });
 
-    let texture_handle = asset_server.load("Character placeholder.png");
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 64.0), 24, 5, None, None);
-
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
     commands.spawn(PlayerBundle {
         marker: Player,
         camera_follow: CameraFollow::default(),
         movement: Movement { speed_x: 2.0, speed_y: 2.0, input: None },
         direction: SpriteDirection::default(),
         sprite_sheet: SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
+            texture_atlas: sprites.player.clone(),
             sprite: TextureAtlasSprite::new(3),
             transform: Transform {
                 translation: Vec3::new(0.0, 0.0, 5.0),
@@ -336,7 +342,7 @@ pub fn factory_setup(
         container: ItemContainer { 
             items: Vec::new(),
             item_type: None,
-            max_items: 1,
+            max_items: PLAYER_CARRY_CAPACITY,
             start_transform: Transform::from_xyz(-16.0, 8.0, 6.0),
             ..default()
         },
@@ -349,15 +355,11 @@ pub fn factory_setup(
         }}
     });
 
-    let mut output_bundle = ContainerOutputSelectorBundle::new(asset_server.clone());
-    output_bundle.sprite.transform.translation = Vec3::new(0.0, -42.0, 1.0);
-    output_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI);
-    let output_entity = commands.spawn(output_bundle).id();
-    let mut item_imports = ItemImportBundle::from_translation(vec3(4.0 * TILE_SIZE.x, 8.0 * TILE_SIZE.y, -1.0), &sprites);
+    let mut item_imports = ItemImportBundle::from_translation(vec3(4.0 * TILE_SIZE.x, 8.0 * TILE_SIZE.y, -1.0), sprites);
     for _ in 0..5 {
         if let Err(_) = item_imports.container.add_item(
             (
-                Some(Item::Resource(ResourceItem::Wood).spawn_bundle(&mut commands, sprites.as_ref()).id()),
+                Some(Item::Resource(ResourceItem::Wood).spawn_bundle(commands, sprites).id()),
                 Some(Item::Resource(ResourceItem::Wood))
             )
         ) {
@@ -365,20 +367,14 @@ pub fn factory_setup(
             break;
         }
     }
-    commands.spawn(item_imports)
-        .push_children(&[output_entity]);
-
-    let mut input_bundle = ContainerInputSelectorBundle::new(asset_server.clone());
-    input_bundle.sprite.transform.translation = Vec3::new(0.0, 42.0, 1.0);
-    input_bundle.sprite.transform.rotation = Quat::from_rotation_z(std::f32::consts::PI);
-    let input_entity = commands.spawn(input_bundle).id();
+    spawn_item_import(commands, sprites, item_imports);
 
-    commands.spawn(ItemExportBundle::from_translation(vec3(-14.0 * TILE_SIZE.x, -16.0 * TILE_SIZE.y, -1.0), &sprites)).push_children(&[input_entity]);
+    spawn_item_export(commands, sprites, ItemExportBundle::from_translation(vec3(-14.0 * TILE_SIZE.x, -16.0 * TILE_SIZE.y, -1.0), sprites));
 }
 
 pub fn reset_factory(
     mut commands: Commands,
-    mut q_workers: Query<(&mut Transform, &mut Job, &mut ItemContainer, &Children, Entity), (With<Worker>, Without<Player>)>,
+    mut q_workers: Query<(&mut Transform, &mut Job, &mut ItemContainer, &mut WorkerFatigue, Option<&Children>, Entity), (With<Worker>, Without<Player>)>,
     mut q_player: Query<(&mut Transform, &mut ItemContainer, Entity), (With<Player>, Without<Worker>)>,
     mut player_state: ResMut<NextState<PlayerState>>,
 ) {
@@ -391,9 +387,14 @@ pub fn reset_factory(
 
     player_state.set(PlayerState::None);
 
-    for (mut worker_transform, mut worker_job, mut container, children, entity) in q_workers.iter_mut() {
-        commands.entity(entity).remove_children(children);
+    for (mut worker_transform, mut worker_job, mut container, mut fatigue, children, entity) in q_workers.iter_mut() {
+        // Idle workers carrying nothing have no children but still need their rest
+        if let Some(children) = children {
+            commands.entity(entity).remove_children(children);
+        }
         container.items.clear();
+        // Workers rest overnight
+        fatigue.0 = 0.0;
 
         for mut point in worker_job.path.iter_mut() {
             point.job_status = JobStatus::Active;
@@ -0,0 +1,148 @@
+use std::ops::{Range, RangeInclusive};
+
+use rand::Rng;
+
+use crate::*;
+
+const CONTRACT_OFFER_COUNT: usize = 3;
+const MAX_ACTIVE_CONTRACTS: usize = 3;
+// Amounts come in steps of five
+const CONTRACT_AMOUNT_STEPS: RangeInclusive<usize> = 2..=8;
+const CONTRACT_DAYS: RangeInclusive<i32> = 2..=6;
+// Buyers pay over the base price for a guaranteed delivery
+const CONTRACT_PREMIUM: Range<f32> = 1.1..1.4;
+// Share of the reward charged as upkeep when a contract is missed
+const CONTRACT_PENALTY: f32 = 0.25;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Contract {
+    pub item: PurchasableItem,
+    pub amount: usize,
+    pub delivered: usize,
+    // Items have to reach an export depot before the night after this day
+    pub due_day: i32,
+    pub reward: f32,
+    pub penalty: f32,
+}
+impl Contract {
+    pub fn random(rng: &mut impl Rng, economy: &Economy, day: i32) -> Option<Contract> {
+        // Buyers want processed goods, not the raw wood that gets imported
+        let mut items = economy.prices.keys()
+            .copied()
+            .filter(|item| *item != PurchasableItem::Resource(ResourceItem::Wood))
+            .collect::<Vec<_>>();
+        items.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        if items.is_empty() {
+            return None;
+        }
+        let item = items[rng.gen_range(0..items.len())];
+        let base_price = economy.prices.get(&item)?.base_price;
+        let amount = rng.gen_range(CONTRACT_AMOUNT_STEPS) * 5;
+        let reward = (amount as f32 * base_price * rng.gen_range(CONTRACT_PREMIUM)).round();
+        Some(Contract {
+            item,
+            amount,
+            delivered: 0,
+            due_day: day + rng.gen_range(CONTRACT_DAYS),
+            reward,
+            penalty: (reward * CONTRACT_PENALTY).round(),
+        })
+    }
+
+    pub fn get_remaining(&self) -> usize {
+        self.amount.saturating_sub(self.delivered)
+    }
+
+    pub fn get_description(&self) -> String {
+        format!("{} x{} by day {} for ${:.2}", self.item.get_name(), self.amount, self.due_day, self.reward)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContractResult {
+    pub contract: Contract,
+    pub completed: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct Contracts {
+    // Offers can only be accepted at night and are replaced every night
+    pub offers: Vec<Contract>,
+    pub active: Vec<Contract>,
+    // Contracts completed or failed last night
+    pub results: Vec<ContractResult>,
+}
+
+#[derive(Event)]
+pub struct AcceptContractEvent {
+    pub offer: usize
+}
+
+pub fn refresh_contract_offers(
+    mut contracts: ResMut<Contracts>,
+    mut rng: ResMut<GameRng>,
+    economy: Res<Economy>,
+    day_timer: Res<DayTimer>,
+) {
+    contracts.offers = (0..CONTRACT_OFFER_COUNT)
+        .filter_map(|_| Contract::random(&mut *rng, &economy, day_timer.day_count))
+        .collect();
+}
+
+pub fn accept_contract(
+    mut ev_accept: EventReader<AcceptContractEvent>,
+    mut contracts: ResMut<Contracts>,
+) {
+    for ev in ev_accept.read() {
+        if contracts.active.len() >= MAX_ACTIVE_CONTRACTS {
+            println!("Can't take on more than {} contracts", MAX_ACTIVE_CONTRACTS);
+            continue;
+        }
+        if ev.offer >= contracts.offers.len() {
+            continue;
+        }
+        let contract = contracts.offers.remove(ev.offer);
+        contracts.active.push(contract);
+    }
+}
+
+// Runs before the spot market so contracts get first pick of the exports
+pub fn fulfill_contracts(
+    mut commands: Commands,
+    mut contracts: ResMut<Contracts>,
+    mut money: ResMut<PlayerMoney>,
+    mut upkeep_tracker: ResMut<UpkeepTracker>,
+    mut q_depot: Query<&mut ItemContainer, With<ItemExport>>,
+    q_items: Query<&Item>,
+    day_timer: Res<DayTimer>,
+) {
+    contracts.results.clear();
+    // Earliest deadlines are filled first
+    contracts.active.sort_by_key(|contract| contract.due_day);
+
+    for mut container in q_depot.iter_mut() {
+        container.items.retain(|item_entity| {
+            let Some(item_entity) = item_entity else { return true; };
+            let Some(purchasable) = q_items.get(*item_entity).ok().and_then(PurchasableItem::from_item) else { return true; };
+            let Some(contract) = contracts.active.iter_mut()
+                .find(|contract| contract.item == purchasable && contract.get_remaining() > 0) else { return true; };
+            contract.delivered += 1;
+            commands.entity(*item_entity).insert(DespawnLater);
+            false
+        });
+    }
+
+    let mut active = Vec::new();
+    for contract in std::mem::take(&mut contracts.active) {
+        if contract.get_remaining() == 0 {
+            money.add_money(contract.reward);
+            contracts.results.push(ContractResult { contract, completed: true });
+        } else if day_timer.day_count > contract.due_day {
+            upkeep_tracker.upkeep.push(Upkeep(contract.penalty, UpkeepSource::Contract));
+            contracts.results.push(ContractResult { contract, completed: false });
+        } else {
+            active.push(contract);
+        }
+    }
+    contracts.active = active;
+}
@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+use bevy::utils::HashMap;
+
+use crate::*;
+
+// Days of history kept per item
+pub const PRICE_HISTORY_DAYS: usize = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PriceRecord {
+    pub day: i32,
+    pub price: f32,
+    pub supply: f32,
+    pub demand: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct PriceHistory {
+    pub records: HashMap<PurchasableItem, VecDeque<PriceRecord>>,
+    // Item shown in the night screen chart
+    pub selected: Option<PurchasableItem>,
+}
+impl PriceHistory {
+    pub fn record(&mut self, day: i32, economy: &Economy) {
+        for item in economy.get_sorted_items() {
+            let Some(price) = economy.prices.get(&item) else { continue; };
+            let records = self.records.entry(item).or_default();
+            records.push_back(PriceRecord {
+                day,
+                price: price.current_price,
+                supply: price.supply,
+                demand: price.demand,
+            });
+            while records.len() > PRICE_HISTORY_DAYS {
+                records.pop_front();
+            }
+        }
+    }
+
+    pub fn get(&self, item: &PurchasableItem) -> Option<&VecDeque<PriceRecord>> {
+        self.records.get(item)
+    }
+}
+
+pub fn record_price_history(
+    mut price_history: ResMut<PriceHistory>,
+    economy: Res<Economy>,
+    day_timer: Res<DayTimer>,
+) {
+    price_history.record(day_timer.day_count, &economy);
+}
@@ -0,0 +1,176 @@
+use std::ops::Range;
+
+use rand::Rng;
+
+use crate::*;
+
+// Share of the gap to baseline supply and demand the rest of the market closes each day, scaled by the item's weights
+const MARKET_RECOVERY: f32 = 0.25;
+// Supply, demand and price stay within this many times their baseline
+const MARKET_MAX_MULTIPLIER: f32 = 4.0;
+// Cap for supply and demand with little or no baseline, like paper which nobody else makes
+const MARKET_MIN_CAP: f32 = 50.0;
+const PRICE_INCREASE_MULT: Range<f32> = 1.01..1.03;
+const PRICE_DECREASE_MULT: Range<f32> = 0.97..0.995;
+
+fn get_cap(base: f32) -> f32 {
+    (base * MARKET_MAX_MULTIPLIER).max(MARKET_MIN_CAP)
+}
+
+impl EconomyPrice {
+    // One night of trading outside the player's factory
+    pub fn step(&mut self, rng: &mut impl Rng) {
+        self.supply += (self.base_supply - self.supply) * (MARKET_RECOVERY * self.supply_weight).min(1.0);
+        self.demand += (self.base_demand - self.demand) * (MARKET_RECOVERY * self.demand_weight).min(1.0);
+        self.clamp_supply_demand();
+
+        // Prices drift a few percent a day without overshooting where the market wants them
+        let target = self.get_target_price();
+        if self.current_price < target {
+            self.current_price = (self.current_price * rng.gen_range(PRICE_INCREASE_MULT)).min(target);
+        } else if self.current_price > target {
+            self.current_price = (self.current_price * rng.gen_range(PRICE_DECREASE_MULT)).max(target);
+        }
+    }
+
+    // Scarce supply or extra demand pull the price above base_price, a glut pulls it below
+    pub fn get_target_price(&self) -> f32 {
+        let pressure = (self.demand + 1.0) / (self.supply + 1.0);
+        let base_pressure = (self.base_demand + 1.0) / (self.base_supply + 1.0);
+        let target = self.base_price * (pressure / base_pressure).sqrt();
+        target.clamp(self.base_price / MARKET_MAX_MULTIPLIER, self.base_price * MARKET_MAX_MULTIPLIER)
+    }
+
+    pub fn buy(&mut self, amount: i32) -> Result<(), &'static str> {
+        if self.supply < amount as f32 {
+            return Err("Not enough supply");
+        }
+        self.supply -= amount as f32;
+        self.demand += amount as f32;
+        self.clamp_supply_demand();
+        Ok(())
+    }
+
+    pub fn sell(&mut self, amount: i32) -> Result<(), &'static str> {
+        if self.demand < amount as f32 {
+            return Err("Not enough demand");
+        }
+        self.demand -= amount as f32;
+        self.supply += amount as f32;
+        self.clamp_supply_demand();
+        Ok(())
+    }
+
+    fn clamp_supply_demand(&mut self) {
+        self.supply = self.supply.clamp(0.0, get_cap(self.base_supply));
+        self.demand = self.demand.clamp(0.0, get_cap(self.base_demand));
+    }
+}
+
+impl Economy {
+    // Items sorted by name, anything drawing from GameRng per item has to go in this order to replay a seed
+    pub fn get_sorted_items(&self) -> Vec<PurchasableItem> {
+        let mut items = self.prices.keys().copied().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        items
+    }
+
+    pub fn step(&mut self, rng: &mut impl Rng) {
+        for item in self.get_sorted_items() {
+            if let Some(price) = self.prices.get_mut(&item) {
+                price.step(rng);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn paper_price() -> EconomyPrice {
+        Economy::default().prices[&PurchasableItem::Good(GoodItem::Paper)].clone()
+    }
+
+    #[test]
+    fn price_converges_to_base_price() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for (item, mut price) in Economy::default().prices.into_iter() {
+            price.current_price = price.base_price * 3.0;
+            price.supply = 0.0;
+            price.demand = get_cap(price.base_demand);
+            for _ in 0..1000 {
+                price.step(&mut rng);
+            }
+            let error = (price.current_price - price.base_price).abs() / price.base_price;
+            assert!(error < 0.02, "{:?} ended at {} instead of {}", item, price.current_price, price.base_price);
+        }
+    }
+
+    #[test]
+    fn supply_and_demand_stay_bounded() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut price = paper_price();
+        price.supply = 1_000_000.0;
+        price.demand = -50.0;
+        price.step(&mut rng);
+        assert!(price.supply <= get_cap(price.base_supply));
+        assert!(price.demand >= 0.0);
+
+        price.demand = get_cap(price.base_demand);
+        while price.sell(1).is_ok() {}
+        assert!(price.supply <= get_cap(price.base_supply));
+        assert!(price.demand >= 0.0);
+    }
+
+    #[test]
+    fn selling_reduces_demand() {
+        let mut price = paper_price();
+        let demand = price.demand;
+        price.sell(5).unwrap();
+        assert_eq!(price.demand, demand - 5.0);
+
+        price.demand = 0.5;
+        assert!(price.sell(1).is_err());
+    }
+
+    #[test]
+    fn buying_reduces_supply() {
+        let mut price = Economy::default().prices[&PurchasableItem::Resource(ResourceItem::Wood)].clone();
+        let supply = price.supply;
+        let demand = price.demand;
+        price.buy(5).unwrap();
+        assert_eq!(price.supply, supply - 5.0);
+        assert_eq!(price.demand, demand + 5.0);
+
+        price.supply = get_cap(price.base_supply);
+        while price.buy(1).is_ok() {}
+        assert!(price.supply >= 0.0);
+        assert!(price.demand <= get_cap(price.base_demand));
+
+        price.supply = 0.5;
+        assert!(price.buy(1).is_err());
+    }
+
+    #[test]
+    fn same_seed_gives_same_prices() {
+        // A map with a different capacity iterates its items in a different order
+        let mut first = Economy::default();
+        let mut prices = HashMap::with_capacity(64);
+        prices.extend(Economy::default().prices);
+        let mut second = Economy { prices };
+
+        let mut first_rng = StdRng::seed_from_u64(11);
+        let mut second_rng = StdRng::seed_from_u64(11);
+        for _ in 0..20 {
+            first.step(&mut first_rng);
+            second.step(&mut second_rng);
+        }
+        for item in first.get_sorted_items() {
+            assert_eq!(first.prices[&item].current_price, second.prices[&item].current_price, "{:?}", item);
+        }
+    }
+}
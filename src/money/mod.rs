@@ -1,35 +1,41 @@
-use std::ops::Range;
-
 use bevy::utils::HashMap;
 use bevy_inspector_egui::{InspectorOptions, inspector_options::ReflectInspectorOptions};
 
 use crate::*;
 
-use rand::{thread_rng, Rng};
-
 mod upkeep;
 pub use upkeep::*;
 
-// TODO: Per item
-const MARKET_FORCE: f32 = 1.25;
-const PRICE_INCREASE_MULT: Range<f32> = 1.01..1.03;
-const PRICE_DECREASE_MULT: Range<f32> = 0.97..0.995;
+mod market;
 
-const MARKET_SELL_PERCENTAGE: f32 = 0.45;
+mod history;
+pub use history::*;
+
+mod contracts;
+pub use contracts::*;
 
 pub struct MoneyPlugin;
 
 impl Plugin for MoneyPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(OnEnter(DayCycleState::Night), (market_forces, market_system))
+            // Systems drawing from GameRng or changing Economy run in a fixed order so a seed always replays the same
+            .add_systems(OnEnter(DayCycleState::Night), (
+                fulfill_contracts.before(sell_export_items),
+                market_system.after(sell_export_items),
+                record_price_history,
+                refresh_contract_offers,
+            ).chain().after(advance_day))
             .add_systems(OnEnter(DayCycleState::Night), (factory_upkeep, living_expenses, item_storage_fee.after(sell_export_items)))
+            .add_systems(Update, accept_contract.run_if(in_state(DayCycleState::Night)))
             .add_systems(OnEnter(DayCycleState::Day), upkeep_system)
             .insert_resource(PlayerMoney {
                 amount: 400.0
             })
-            .insert_resource(MarketTimer::default())
             .insert_resource(Economy::default())
+            .init_resource::<PriceHistory>()
+            .init_resource::<Contracts>()
+            .add_event::<AcceptContractEvent>()
             .insert_resource(AssemblyPrices::default())
             .insert_resource(UpkeepTimer::default())
             .insert_resource(UpkeepTracker::new())
@@ -62,7 +68,7 @@ pub trait Money {
     fn try_remove_money(&mut self, amount: f32) -> Result<(), &str>;
 }
 
-#[derive(Reflect)]
+#[derive(Reflect, Clone, Serialize, Deserialize)]
 pub struct EconomyPrice {
     pub current_price: f32,
     // Price that is considered baseline and should fluctuate around this value
@@ -99,13 +105,20 @@ impl Default for AssemblyPrices {
     }
 }
 
-#[derive(Reflect, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(Reflect, Hash, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PurchasableItem {
     Good(GoodItem),
     Resource(ResourceItem)
 }
 
 impl PurchasableItem {
+    pub fn from_item(item: &Item) -> Option<PurchasableItem> {
+        match item {
+            Item::Good(good) => Some(PurchasableItem::Good(*good)),
+            Item::Resource(resource) => Some(PurchasableItem::Resource(*resource)),
+            Item::Material(_) => None
+        }
+    }
     pub fn get_price(&self, economy: &Economy) -> Option<f32> {
         economy.prices.get(self).map(|x| { x.current_price })
     }
@@ -204,12 +217,7 @@ impl Purchasable for Item {
         };
         let Some(purchasable) = purchasable else { return Err("Item not purchasable"); };
         let Some(price) = economy.prices.get_mut(&purchasable) else { return Err("Item not purchasable"); };
-        if (price.supply as i32) < amount {
-            return Err("Not enough supply");
-        }
-        price.supply -= amount as f32;
-
-        Ok(())
+        price.buy(amount)
     }
     fn sell(&mut self, economy: &mut Economy, amount: i32) -> Result<(), &'static str> {
         let purchasable = match self {
@@ -219,13 +227,7 @@ impl Purchasable for Item {
         };
         let Some(purchasable) = purchasable else { return Err("Item not purchasable"); };
         let Some(price) = economy.prices.get_mut(&purchasable) else { return Err("Item not purchasable"); };
-        if (price.demand as i32) < amount {
-            return Err("Not enough demand");
-        }
-
-        price.supply += amount as f32;
-
-        Ok(())
+        price.sell(amount)
     }
     fn get_supply(&self, economy: &Economy) -> Option<f32> {
         let purchasable = match self {
@@ -251,63 +253,9 @@ impl Purchasable for Item {
     }
 }
 
-#[derive(Resource)]
-struct MarketTimer(Timer);
-impl Default for MarketTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(10.0, TimerMode::Repeating))
-    }
-}
-
-fn market_system(
+pub fn market_system(
     mut economy: ResMut<Economy>,
-    time: Res<Time>,
-    mut market_timer: ResMut<MarketTimer>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = thread_rng();
-    for (item, price) in economy.prices.iter_mut() {
-        let supply = price.supply - price.base_supply;
-        let demand = price.demand - price.base_demand;
-        let price_gap = price.current_price / price.base_price;
-        let supply_gap = demand / supply;
-        if item == &PurchasableItem::Good(GoodItem::Paper) {
-            println!("{} {} {} {}", supply, demand, price_gap, supply_gap);
-        }
-
-        if price_gap < supply_gap {
-            price.current_price *= rng.gen_range(PRICE_INCREASE_MULT);
-        } else if price_gap > supply_gap {
-            price.current_price *= rng.gen_range(PRICE_DECREASE_MULT);
-        }
-    }
-}
-
-fn market_forces(
-    mut economy: ResMut<Economy>,
-    time: Res<Time>,
-    mut market_timer: ResMut<MarketTimer>,
-) {
-    let mut rng = thread_rng();
-    for (item, price) in economy.prices.iter_mut() {
-        let price_gap = (price.current_price / price.base_price).max(price.base_price / price.current_price);
-
-        let supply = price.supply - price.base_supply;
-        let demand = price.demand - price.base_demand;
-        // TODO: Min max values for supply & demand
-
-        let weighted_supply = supply * price.demand_weight;
-        let weighted_demand = demand * price.supply_weight;
-        if weighted_supply >= weighted_demand {
-            price.demand += MARKET_FORCE * price_gap * price.demand_weight;
-        }
-        if weighted_supply < weighted_demand {
-            price.supply += MARKET_FORCE * price_gap * price.supply_weight;
-        }
-        
-        if price.supply > 1.0 && price.demand > 1.0 {
-            let sold = (supply).min(price.demand * MARKET_SELL_PERCENTAGE).floor();
-            price.supply -= sold;
-            price.demand -= sold;
-        }
-    }
+    economy.step(&mut *rng);
 }
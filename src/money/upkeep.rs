@@ -6,7 +6,6 @@ const FACTORY_COST: f32 = 4.5;
 const LIVING_EXPENSE_BASE: f32 = 0.25;
 
 pub const STORAGE_FEE: f32 = 0.05;
-pub const WORKER_UPKEEP: f32 = 0.6;
 
 #[derive(Resource)]
 pub struct UpkeepTimer(Timer);
@@ -16,15 +15,19 @@ impl Default for UpkeepTimer {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Reflect)]
+#[derive(PartialEq, Clone, Copy, Reflect, Serialize, Deserialize)]
 pub enum UpkeepSource {
     Factory,
+    // Flat worker upkeep from older saves
     Worker,
     Living,
-    Storage
+    Storage,
+    Wages,
+    // Penalty for a missed delivery contract
+    Contract
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Upkeep (pub f32, pub UpkeepSource);
 
 #[derive(Resource)]
@@ -39,20 +42,20 @@ impl UpkeepTracker {
         };
         upkeep
     }
-    fn calculate_worker_upkeep(&mut self, workers: i32) {
-        self.upkeep = self.upkeep.iter().filter(|x| x.1 != UpkeepSource::Worker).map(|x| *x).collect::<Vec<_>>();
-        for _ in 0..workers {
-            self.upkeep.push(Upkeep(WORKER_UPKEEP, UpkeepSource::Worker));
+    fn calculate_worker_upkeep(&mut self, wages: Vec<f32>) {
+        self.upkeep = self.upkeep.iter().filter(|x| x.1 != UpkeepSource::Worker && x.1 != UpkeepSource::Wages).copied().collect::<Vec<_>>();
+        for wage in wages {
+            self.upkeep.push(Upkeep(wage, UpkeepSource::Wages));
         }
     }
 }
 
 pub fn factory_upkeep(
     mut upkeep_tracker: ResMut<UpkeepTracker>,
-    q_workers: Query<&Worker>,
+    q_workers: Query<&WorkerStats, With<Worker>>,
 ) {
     upkeep_tracker.upkeep.push(Upkeep(FACTORY_COST, UpkeepSource::Factory));
-    upkeep_tracker.calculate_worker_upkeep(q_workers.iter().count() as i32);
+    upkeep_tracker.calculate_worker_upkeep(q_workers.iter().map(|stats| stats.wage).collect());
 }
 
 pub fn living_expenses(
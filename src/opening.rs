@@ -82,7 +82,8 @@ pub fn opening_dialogue_render(
     mut query: Query<(&mut OpeningDialogueProps, &mut ComputedStyles, &KStyle, &mut OnEvent)>,
     day_state: Res<State<DayCycleState>>,
     opening_dialogue: Res<OpeningDialogue>,
-    
+    game_rng: Res<GameRng>,
+    seed_entry: Res<SeedEntry>,
 ) -> bool {
     if let Ok((props, mut computed_styles, style, mut event)) = query.get_mut(entity) {
         *computed_styles = KStyle::default()
@@ -120,6 +121,19 @@ pub fn opening_dialogue_render(
                             ..default()
                         }}
                     />
+                    <DialogueBundle
+                        props={DialogueProps {
+                            dialogue: match seed_entry.get_seed() {
+                                Some(seed) => format!("Seed: {} (backspace to change)", seed),
+                                None => format!("Seed: {} (type digits to change)", game_rng.get_seed()),
+                            }
+                        }}
+                        styles={KStyle {
+                            font_size: StyleProp::Value(32.0),
+                            padding_top: Units::Pixels(64.0).into(),
+                            ..default()
+                        }}
+                    />
                 // </NinePatchBundle>
             </BackgroundBundle>
         );
@@ -102,9 +102,10 @@ pub fn player_movement(
 }
 
 pub fn move_entities (
-    mut q_movement: Query<(&Movement, &mut Transform, Option<&mut SpriteDirection>)>,
+    mut q_movement: Query<(&Movement, &mut Transform, Option<&mut SpriteDirection>, Option<&Player>)>,
+    q_walls: Query<&Transform, (With<Wall>, Without<Movement>)>,
 ) {
-    for (Movement { mut input, speed_x, speed_y }, mut transform, sprite_direction) in q_movement.iter_mut() {
+    for (Movement { mut input, speed_x, speed_y }, mut transform, sprite_direction, player) in q_movement.iter_mut() {
         if let Some(input_vec) = input {
             let Vec2 { x, y } = input_vec.normalize_or_zero();
             let mut movement: Vec3 = Vec3 {
@@ -119,7 +120,23 @@ pub fn move_entities (
                 movement.x = dist * movement.x.signum();
                 movement.y = dist * movement.y.signum();
             }
-            transform.translation += movement;
+            // The player can't walk through walls, sliding along them instead, but can leave one built on top of them
+            let blocked = |pos: Vec3| player.is_some() && q_walls.iter().any(|wall| {
+                (wall.translation.xy() - pos.xy()).abs().cmplt(Vec2::new(TILE_SIZE.x, TILE_SIZE.y) * 0.75).all()
+            });
+            let mut next = transform.translation + movement;
+            if blocked(next) && !blocked(transform.translation) {
+                let x_only = transform.translation + Vec3::new(movement.x, 0.0, 0.0);
+                let y_only = transform.translation + Vec3::new(0.0, movement.y, 0.0);
+                next = if !blocked(x_only) {
+                    x_only
+                } else if !blocked(y_only) {
+                    y_only
+                } else {
+                    transform.translation
+                };
+            }
+            transform.translation = next;
 
             if let Some(mut direction) = sprite_direction {
                 direction.set_from_vec(input_vec);
@@ -132,6 +149,7 @@ pub fn move_entities (
     }
 }
 const PLAYER_REACH: f32 = 4.0 * TILE_SIZE.x;
+pub const PLAYER_CARRY_CAPACITY: usize = 4;
 
 // TODO: Refactor with one-shot systems once you can use 0.12
 // TODO: Resource & Trait for closest interactable
@@ -146,6 +164,12 @@ pub fn player_pickup_item(
 ) {
     if input.just_pressed(KeyCode::F) {
         let Ok((player, player_transform, mut player_container, children)) = q_player.get_single_mut() else { return };
+        // The carried stack only holds one item type
+        let carried_type = player_container.items.first().copied().flatten()
+            .and_then(|item| q_items.get(item).ok())
+            .map(|(_, _, _, item_type)| *item_type);
+        // Shift always picks up so a stack can be filled next to a container
+        let stacking = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
         let near_item = q_items.iter_mut()
             .filter(|i| {
                 let is_input = q_io_containers.iter_mut().any(|c| c.0.input.items.contains(&Some(i.0)));
@@ -190,7 +214,7 @@ pub fn player_pickup_item(
         let io_container_dist = near_io_container.as_ref().map(|c| Vec3::distance(c.1.translation, player_transform.translation));
         let item_dist = near_item.as_ref().map(|i| Vec3::distance(i.1.translation(), player_transform.translation));
 
-        if near_io_container.is_some() && !player_container.items.is_empty() && (container_dist.is_none() || io_container_dist.unwrap() < container_dist.unwrap()) {
+        if !stacking && near_io_container.is_some() && !player_container.items.is_empty() && (container_dist.is_none() || io_container_dist.unwrap() < container_dist.unwrap()) {
             println!("Dropping item in IO container");
             if let Some((mut container, _, container_entity)) = near_io_container {
                 let Some(Some(child)) = children.map(|c| c.first()) else { return; };
@@ -217,7 +241,7 @@ pub fn player_pickup_item(
                     }
                 }
             }
-        } else if near_container.is_some() && !player_container.items.is_empty() {
+        } else if !stacking && near_container.is_some() && !player_container.items.is_empty() {
             println!("Dropping item in container");
             if let Some((container, _, container_entity)) = near_container.as_mut() {
                 let Some(Some(child)) = children.map(|c| c.first()) else { return; };
@@ -257,6 +281,11 @@ pub fn player_pickup_item(
             let io_container = q_io_containers.iter_mut().find(|c| c.0.output.items.contains(&Some(entity)));
     
 
+            if carried_type.is_some_and(|carried| carried != *item_type) {
+                println!("Can only carry one item type");
+                return;
+            }
+
             if let Ok(_) = player_container.add_item((Some(entity), Some(*item_type))) {
                 if let Some((mut item_container, _, _)) = item_container {
                     if let Err(err) = item_container.remove_item(Some(entity)) {
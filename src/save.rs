@@ -0,0 +1,577 @@
+use std::{fs, path::Path};
+
+use bevy::utils::HashMap;
+
+use crate::*;
+
+pub const SAVE_PATH: &str = "saves/factory.ron";
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(DayCycleState::Night), save_game
+                .after(advance_day)
+                .after(reset_factory)
+                .after(sell_export_items)
+                .after(item_storage_fee)
+                .after(factory_upkeep)
+                .after(living_expenses)
+                .after(market_system)
+                .after(fulfill_contracts)
+                .after(record_price_history)
+                .after(refresh_contract_offers)
+                .after(refresh_worker_candidates)
+            )
+            .add_systems(Update, input_load_game.run_if(in_state(DayCycleState::Opening)))
+            .add_systems(PostUpdate, load_game)
+            .add_event::<LoadGameEvent>()
+            .init_resource::<SaveFile>()
+        ;
+    }
+}
+
+// Entities are saved as indexes into the saved lists so job steps can be relinked on load
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SavedEntity {
+    Assembly(usize),
+    Import(usize),
+    Export(usize),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum SavedJobAction {
+    Work {
+        assembly: SavedEntity
+    },
+    ContainerPickup {
+        container: SavedEntity,
+        pickup_amount: i32,
+        #[serde(default)]
+        filter: ItemFilter
+    },
+    Drop {
+        input_container: Option<SavedEntity>
+    },
+    WaitForItems {
+        container: SavedEntity,
+        amount: usize
+    },
+    Goto {
+        step: usize
+    },
+    StopIfFull {
+        container: SavedEntity
+    },
+    Zone {
+        zone: ZoneType
+    },
+    Idle
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedJobPoint {
+    pub id: u32,
+    pub point: [u32; 2],
+    pub point_size: [i32; 2],
+    pub action: SavedJobAction,
+    #[serde(default)]
+    pub condition: JobCondition,
+    pub timer: Option<f32>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedWorker {
+    pub position: [f32; 3],
+    pub state: WorkerState,
+    pub items: Vec<Item>,
+    pub path: Vec<SavedJobPoint>,
+    #[serde(default)]
+    pub stats: WorkerStats,
+    #[serde(default)]
+    pub profile: WorkerProfile,
+    #[serde(default)]
+    pub xp: f32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedAssembly {
+    pub assembly_type: AssemblyType,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub recipe: usize,
+    pub input: Vec<Item>,
+    pub output: Vec<Item>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedDepot {
+    pub position: [f32; 3],
+    pub items: Vec<Item>,
+    // Only used by export depots
+    #[serde(default)]
+    pub rules: Vec<(PurchasableItem, SellRule)>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedConveyor {
+    pub position: [f32; 3],
+    pub direction: ConveyorDirection,
+    pub speed: f32,
+    pub item: Option<Item>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedCable {
+    pub position: [f32; 3],
+    pub power_type: Power
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedPlayer {
+    pub position: [f32; 3],
+    pub items: Vec<Item>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub money: f32,
+    pub day_count: i32,
+    pub economy: Vec<(PurchasableItem, EconomyPrice)>,
+    pub upkeep: Vec<Upkeep>,
+    pub player: SavedPlayer,
+    pub assemblies: Vec<SavedAssembly>,
+    pub imports: Vec<SavedDepot>,
+    pub exports: Vec<SavedDepot>,
+    pub workers: Vec<SavedWorker>,
+    #[serde(default)]
+    pub conveyors: Vec<SavedConveyor>,
+    #[serde(default)]
+    pub cables: Vec<SavedCable>,
+    #[serde(default)]
+    pub job_templates: Vec<JobTemplate>,
+    #[serde(default)]
+    pub walls: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub doors: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub zones: Vec<([u32; 2], ZoneType)>,
+    #[serde(default)]
+    pub contracts: Vec<Contract>,
+    #[serde(default)]
+    pub price_history: Vec<(PurchasableItem, Vec<PriceRecord>)>,
+    // Older saves without a seed keep whatever sequence the game started with
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub rng_draws: u64
+}
+
+#[derive(Resource)]
+pub struct SaveFile {
+    pub path: String
+}
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self {
+            path: SAVE_PATH.to_string()
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct LoadGameEvent {
+    pub path: String
+}
+
+fn get_container_items(container: &ItemContainer, q_items: &Query<&Item>) -> Vec<Item> {
+    container.items.iter()
+        .filter_map(|item| item.and_then(|entity| q_items.get(entity).ok()))
+        .copied()
+        .collect()
+}
+
+fn spawn_container_items(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    container: &mut ItemContainer,
+    items: &[Item]
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for item in items.iter() {
+        let transform = container.get_transform();
+        let item_entity = item.spawn_bundle_with_transform(commands, transform, sprites).id();
+        match container.add_item((Some(item_entity), Some(*item))) {
+            Ok(_) => entities.push(item_entity),
+            Err(e) => {
+                println!("Error loading item into container: {:?}", e);
+                commands.entity(item_entity).despawn_recursive();
+            }
+        }
+    }
+    entities
+}
+
+pub fn save_game(
+    save_file: Res<SaveFile>,
+    money: Res<PlayerMoney>,
+    day_timer: Res<DayTimer>,
+    economy: Res<Economy>,
+    upkeep_tracker: Res<UpkeepTracker>,
+    (job_templates, contracts, game_rng, price_history): (Res<JobTemplates>, Res<Contracts>, Res<GameRng>, Res<PriceHistory>),
+    floor_zones: Res<FloorZones>,
+    q_player: Query<(&Transform, &ItemContainer), With<Player>>,
+    q_assemblies: Query<(Entity, &AssemblyType, &Transform, &ItemIOContainer, &AssemblyRecipes), With<Assembly>>,
+    q_imports: Query<(Entity, &Transform, &ItemContainer), With<ItemImport>>,
+    q_exports: Query<(Entity, &Transform, &ItemContainer, &ExportSellRules), With<ItemExport>>,
+    q_workers: Query<(&Transform, &WorkerState, &ItemContainer, &Job, &WorkerStats, &WorkerProfile, &WorkerExperience), With<Worker>>,
+    q_conveyors: Query<(&Transform, &Conveyor)>,
+    q_cables: Query<(&Transform, &PowerCable)>,
+    q_floor: Query<(&Transform, Option<&Wall>), Or<(With<Wall>, With<Door>)>>,
+    q_items: Query<&Item>,
+) {
+    let Ok((player_transform, player_container)) = q_player.get_single() else { return };
+    let mut saved_entities: HashMap<Entity, SavedEntity> = HashMap::new();
+
+    let mut assemblies = Vec::new();
+    for (entity, assembly_type, transform, io_container, recipes) in q_assemblies.iter() {
+        saved_entities.insert(entity, SavedEntity::Assembly(assemblies.len()));
+        assemblies.push(SavedAssembly {
+            assembly_type: *assembly_type,
+            position: transform.translation.to_array(),
+            recipe: recipes.selected,
+            input: get_container_items(&io_container.input, &q_items),
+            output: get_container_items(&io_container.output, &q_items)
+        });
+    }
+    let mut imports = Vec::new();
+    for (entity, transform, container) in q_imports.iter() {
+        saved_entities.insert(entity, SavedEntity::Import(imports.len()));
+        imports.push(SavedDepot {
+            position: transform.translation.to_array(),
+            items: get_container_items(container, &q_items),
+            rules: Vec::new()
+        });
+    }
+    let mut exports = Vec::new();
+    for (entity, transform, container, rules) in q_exports.iter() {
+        saved_entities.insert(entity, SavedEntity::Export(exports.len()));
+        exports.push(SavedDepot {
+            position: transform.translation.to_array(),
+            items: get_container_items(container, &q_items),
+            rules: rules.rules.iter().map(|(item, rule)| (*item, *rule)).collect()
+        });
+    }
+
+    let mut workers = Vec::new();
+    for (transform, state, container, job, stats, profile, experience) in q_workers.iter() {
+        let mut path = Vec::new();
+        let mut kept = Vec::new();
+        for (i, job_point) in job.path.iter().enumerate() {
+            let action = match &job_point.action {
+                JobAction::Work { assembly } => {
+                    let Some(assembly) = saved_entities.get(assembly) else { continue };
+                    SavedJobAction::Work { assembly: *assembly }
+                },
+                JobAction::ContainerPickup { container, pickup_amount, filter } => {
+                    let Some(container) = saved_entities.get(container) else { continue };
+                    SavedJobAction::ContainerPickup { container: *container, pickup_amount: *pickup_amount, filter: filter.clone() }
+                },
+                JobAction::Drop { input_container, .. } => SavedJobAction::Drop {
+                    input_container: input_container.and_then(|c| saved_entities.get(&c).copied())
+                },
+                JobAction::WaitForItems { container, amount } => {
+                    let Some(container) = saved_entities.get(container) else { continue };
+                    SavedJobAction::WaitForItems { container: *container, amount: *amount }
+                },
+                JobAction::Goto { step } => SavedJobAction::Goto { step: *step },
+                JobAction::StopIfFull { container } => {
+                    let Some(container) = saved_entities.get(container) else { continue };
+                    SavedJobAction::StopIfFull { container: *container }
+                },
+                JobAction::Zone { zone } => SavedJobAction::Zone { zone: *zone },
+                JobAction::Idle => SavedJobAction::Idle,
+                // Loose items are not saved so there is nothing to relink
+                JobAction::Pickup { .. } => continue,
+            };
+            path.push(SavedJobPoint {
+                id: job_point.id,
+                point: [job_point.point.x, job_point.point.y],
+                point_size: job_point.point_size.to_array(),
+                action,
+                condition: job_point.condition,
+                timer: job_point.timer.as_ref().map(|t| t.duration().as_secs_f32())
+            });
+            kept.push(i);
+        }
+        for saved_point in path.iter_mut() {
+            if let SavedJobAction::Goto { step } = &mut saved_point.action {
+                *step = get_kept_step(&kept, *step);
+            }
+        }
+        workers.push(SavedWorker {
+            position: transform.translation.to_array(),
+            state: *state,
+            items: get_container_items(container, &q_items),
+            path,
+            stats: *stats,
+            profile: profile.clone(),
+            xp: experience.xp
+        });
+    }
+
+    let conveyors = q_conveyors.iter().map(|(transform, conveyor)| SavedConveyor {
+        position: transform.translation.to_array(),
+        direction: conveyor.direction,
+        speed: conveyor.speed,
+        item: conveyor.item.and_then(|item| q_items.get(item).ok()).copied()
+    }).collect();
+
+    let cables = q_cables.iter().map(|(transform, cable)| SavedCable {
+        position: transform.translation.to_array(),
+        power_type: cable.power_type
+    }).collect();
+
+    let (walls, doors): (Vec<_>, Vec<_>) = q_floor.iter().partition(|(_, wall)| wall.is_some());
+
+    let save = SaveGame {
+        money: money.amount,
+        day_count: day_timer.day_count,
+        economy: economy.prices.iter().map(|(item, price)| (*item, price.clone())).collect(),
+        upkeep: upkeep_tracker.upkeep.clone(),
+        player: SavedPlayer {
+            position: player_transform.translation.to_array(),
+            items: get_container_items(player_container, &q_items)
+        },
+        assemblies,
+        imports,
+        exports,
+        workers,
+        conveyors,
+        cables,
+        job_templates: job_templates.templates.clone(),
+        walls: walls.iter().map(|(transform, _)| transform.translation.to_array()).collect(),
+        doors: doors.iter().map(|(transform, _)| transform.translation.to_array()).collect(),
+        zones: floor_zones.zones.iter().map(|(tile, zone)| ([tile.x, tile.y], *zone)).collect(),
+        contracts: contracts.active.clone(),
+        price_history: price_history.records.iter()
+            .map(|(item, records)| (*item, records.iter().copied().collect()))
+            .collect(),
+        seed: Some(game_rng.get_seed()),
+        rng_draws: game_rng.get_draws()
+    };
+
+    let serialized = match ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()) {
+        Ok(serialized) => serialized,
+        Err(e) => {
+            println!("Error serializing save: {:?}", e);
+            return;
+        }
+    };
+    if let Some(dir) = Path::new(&save_file.path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            println!("Error creating save directory: {:?}", e);
+            return;
+        }
+    }
+    if let Err(e) = fs::write(&save_file.path, serialized) {
+        println!("Error writing save: {:?}", e);
+    }
+}
+
+pub fn input_load_game(
+    input: Res<Input<KeyCode>>,
+    save_file: Res<SaveFile>,
+    mut ev_load: EventWriter<LoadGameEvent>,
+) {
+    if input.just_pressed(KeyCode::L) && Path::new(&save_file.path).exists() {
+        ev_load.send(LoadGameEvent { path: save_file.path.clone() });
+    }
+}
+
+pub fn load_game(
+    mut commands: Commands,
+    mut ev_load: EventReader<LoadGameEvent>,
+    sprites: Res<SpriteStorage>,
+    definitions: Res<AssemblyDefinitions>,
+    mut money: ResMut<PlayerMoney>,
+    mut day_timer: ResMut<DayTimer>,
+    mut economy: ResMut<Economy>,
+    mut upkeep_tracker: ResMut<UpkeepTracker>,
+    mut job_templates: ResMut<JobTemplates>,
+    mut floor_zones: ResMut<FloorZones>,
+    (mut contracts, mut game_rng, mut price_history): (ResMut<Contracts>, ResMut<GameRng>, ResMut<PriceHistory>),
+    mut q_player: Query<(Entity, &mut Transform, &mut ItemContainer), With<Player>>,
+    q_factory: Query<Entity, Or<(With<Assembly>, With<Worker>, With<ItemImport>, With<ItemExport>, With<Conveyor>, With<PowerCable>, With<Wall>, With<Door>)>>,
+    q_loose_items: Query<Entity, (With<Item>, Without<Parent>)>,
+    mut next_day_state: ResMut<NextState<DayCycleState>>,
+    mut next_tutorial_state: ResMut<NextState<TutorialState>>,
+) {
+    let Some(ev) = ev_load.read().last() else { return };
+    let save: SaveGame = match fs::read_to_string(&ev.path).map_err(|e| e.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|e| e.to_string())) {
+        Ok(save) => save,
+        Err(e) => {
+            println!("Error loading save: {:?}", e);
+            return;
+        }
+    };
+
+    for entity in q_factory.iter().chain(q_loose_items.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    money.amount = save.money;
+    day_timer.day_count = save.day_count;
+    day_timer.day_timer.reset();
+    for (item, price) in save.economy.into_iter() {
+        economy.prices.insert(item, price);
+    }
+    upkeep_tracker.upkeep = save.upkeep;
+    job_templates.templates = save.job_templates;
+    job_templates.selected = 0;
+    floor_zones.zones = save.zones.iter().map(|(tile, zone)| (TilePos::new(tile[0], tile[1]), *zone)).collect();
+    contracts.active = save.contracts;
+    contracts.offers.clear();
+    contracts.results.clear();
+    price_history.records = save.price_history.into_iter()
+        .map(|(item, records)| (item, records.into_iter().collect()))
+        .collect();
+    if let Some(seed) = save.seed {
+        game_rng.restore(seed, save.rng_draws);
+    }
+
+    if let Ok((player_entity, mut transform, mut container)) = q_player.get_single_mut() {
+        for item in container.items.drain(..).flatten() {
+            commands.entity(item).despawn_recursive();
+        }
+        transform.translation = Vec3::from_array(save.player.position);
+        let items = spawn_container_items(&mut commands, &sprites, &mut container, &save.player.items);
+        commands.entity(player_entity).push_children(&items);
+    }
+
+    let mut assemblies = Vec::new();
+    for saved in save.assemblies.iter() {
+        let position = Vec3::from_array(saved.position);
+        let Some(mut io_container) = saved.assembly_type.get_io_container(&sprites, &definitions) else {
+            println!("Missing assembly definition for {:?}", saved.assembly_type);
+            assemblies.push(None);
+            continue;
+        };
+        let entity = spawn_assembly(&mut commands, &sprites, &definitions, saved.assembly_type, position.xy());
+        if let Some(mut recipes) = saved.assembly_type.get_recipes(&sprites, &definitions) {
+            if saved.recipe < recipes.recipes.len() {
+                recipes.selected = saved.recipe;
+                io_container.input.item_type = recipes.get_selected().get_input_type();
+                commands.entity(entity).insert((recipes.get_selected(), recipes));
+            }
+        }
+        let mut items = spawn_container_items(&mut commands, &sprites, &mut io_container.input, &saved.input);
+        items.extend(spawn_container_items(&mut commands, &sprites, &mut io_container.output, &saved.output));
+        commands.entity(entity).insert(io_container).push_children(&items);
+        assemblies.push(Some(entity));
+    }
+
+    let mut imports = Vec::new();
+    for saved in save.imports.iter() {
+        let mut bundle = ItemImportBundle::from_translation(Vec3::from_array(saved.position), &sprites);
+        let items = spawn_container_items(&mut commands, &sprites, &mut bundle.container, &saved.items);
+        let entity = spawn_item_import(&mut commands, &sprites, bundle);
+        commands.entity(entity).push_children(&items);
+        imports.push(entity);
+    }
+
+    let mut exports = Vec::new();
+    for saved in save.exports.iter() {
+        let mut bundle = ItemExportBundle::from_translation(Vec3::from_array(saved.position), &sprites);
+        bundle.rules.rules = saved.rules.iter().copied().collect();
+        let items = spawn_container_items(&mut commands, &sprites, &mut bundle.items, &saved.items);
+        let entity = spawn_item_export(&mut commands, &sprites, bundle);
+        commands.entity(entity).push_children(&items);
+        exports.push(entity);
+    }
+
+    for saved in save.conveyors.iter() {
+        let position = Vec3::from_array(saved.position);
+        let item = saved.item.map(|item| item.spawn_bundle(&mut commands, &sprites).id());
+        let entity = spawn_conveyor(&mut commands, &sprites, position.xy(), saved.direction, saved.speed, item);
+        if let Some(item) = item {
+            commands.entity(entity).add_child(item);
+        }
+    }
+
+    for saved in save.cables.iter() {
+        spawn_power_cable(&mut commands, &sprites, Vec3::from_array(saved.position).xy(), saved.power_type);
+    }
+
+    for position in save.walls.iter() {
+        spawn_wall(&mut commands, &sprites, Vec3::from_array(*position).xy());
+    }
+    for position in save.doors.iter() {
+        spawn_door(&mut commands, &sprites, Vec3::from_array(*position).xy());
+    }
+
+    let get_entity = |saved: SavedEntity| match saved {
+        SavedEntity::Assembly(i) => assemblies.get(i).copied().flatten(),
+        SavedEntity::Import(i) => imports.get(i).copied(),
+        SavedEntity::Export(i) => exports.get(i).copied(),
+    };
+
+    for saved in save.workers.iter() {
+        let worker_entity = commands.spawn_empty().id();
+        let mut bundle = WorkerBundle::default_with_sprites(&sprites);
+        bundle.sprite.transform.translation = Vec3::from_array(saved.position);
+        bundle.state = saved.state;
+        bundle.stats = saved.stats;
+        bundle.profile = saved.profile.clone();
+        bundle.experience.xp = saved.xp;
+        let mut kept = Vec::new();
+        for (i, saved_point) in saved.path.iter().enumerate() {
+            let action = match &saved_point.action {
+                SavedJobAction::Work { assembly } => {
+                    let Some(assembly) = get_entity(*assembly) else { continue };
+                    JobAction::Work { assembly }
+                },
+                SavedJobAction::ContainerPickup { container, pickup_amount, filter } => {
+                    let Some(container) = get_entity(*container) else { continue };
+                    JobAction::ContainerPickup { container, pickup_amount: *pickup_amount, filter: filter.clone() }
+                },
+                SavedJobAction::Drop { input_container } => JobAction::Drop {
+                    worker: worker_entity,
+                    input_container: input_container.and_then(get_entity)
+                },
+                SavedJobAction::WaitForItems { container, amount } => {
+                    let Some(container) = get_entity(*container) else { continue };
+                    JobAction::WaitForItems { container, amount: *amount }
+                },
+                SavedJobAction::Goto { step } => JobAction::Goto { step: *step },
+                SavedJobAction::StopIfFull { container } => {
+                    let Some(container) = get_entity(*container) else { continue };
+                    JobAction::StopIfFull { container }
+                },
+                SavedJobAction::Zone { zone } => JobAction::Zone { zone: *zone },
+                SavedJobAction::Idle => JobAction::Idle,
+            };
+            bundle.job.path.push(JobPoint {
+                id: saved_point.id,
+                point: TilePos::new(saved_point.point[0], saved_point.point[1]),
+                point_size: IVec2::from_array(saved_point.point_size),
+                job_status: JobStatus::Active,
+                action,
+                condition: saved_point.condition,
+                timer: saved_point.timer.map(|t| Timer::new(Duration::from_secs_f32(t), TimerMode::Once))
+            });
+            kept.push(i);
+        }
+        for job_point in bundle.job.path.iter_mut() {
+            if let JobAction::Goto { step } = &mut job_point.action {
+                *step = get_kept_step(&kept, *step);
+            }
+        }
+        let items = spawn_container_items(&mut commands, &sprites, &mut bundle.worker_items, &saved.items);
+        commands.entity(worker_entity).insert(bundle).push_children(&items);
+    }
+
+    next_tutorial_state.set(TutorialState::Disabled);
+    next_day_state.set(DayCycleState::Day);
+}
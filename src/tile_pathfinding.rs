@@ -1,30 +1,336 @@
+use bevy::utils::HashMap;
 use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 
 use crate::*;
 
+// Cleared when it grows past this instead of tracking which paths are stale
+const PATH_CACHE_LIMIT: usize = 4096;
+
+// Steps are measured in tenths of a tile so diagonals can cost about sqrt(2)
+const STRAIGHT_STEP_COST: u32 = 10;
+const DIAGONAL_STEP_COST: u32 = 14;
+// Tile costs multiply the step cost, walkways go below the default so workers prefer them
+pub const DEFAULT_TILE_COST: u32 = 2;
+const MIN_TILE_COST: u32 = 1;
+// Added for each worker standing on a tile when a blocked worker finds a new route
+const CONGESTION_COST: u32 = 4;
+
+// Solid tiles as one bit per tile over the whole map
+#[derive(Resource)]
+pub struct CollisionGrid {
+    pub size: TilemapSize,
+    bits: Vec<u64>,
+    // Cost of walking onto each tile, set by painted zones
+    costs: Vec<u8>,
+    // Tiles covered by each solid entity, so moved and removed entities can be cleared
+    entity_tiles: HashMap<Entity, Vec<TilePos>>,
+}
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        CollisionGrid::new(GRID_SIZE)
+    }
+}
+impl CollisionGrid {
+    pub fn new(size: TilemapSize) -> Self {
+        let tile_count = (size.x * size.y) as usize;
+        CollisionGrid {
+            size,
+            bits: vec![0; tile_count.div_ceil(64)],
+            costs: vec![DEFAULT_TILE_COST as u8; tile_count],
+            entity_tiles: HashMap::new(),
+        }
+    }
+
+    fn get_index(&self, pos: &TilePos) -> Option<usize> {
+        if pos.x >= self.size.x || pos.y >= self.size.y {
+            return None;
+        }
+        Some((pos.y * self.size.x + pos.x) as usize)
+    }
+
+    pub fn is_solid(&self, pos: &TilePos) -> bool {
+        self.get_index(pos).is_some_and(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    pub fn get_cost(&self, pos: &TilePos) -> u32 {
+        self.get_index(pos).map_or(DEFAULT_TILE_COST, |i| self.costs[i] as u32)
+    }
+
+    pub fn set_cost(&mut self, pos: &TilePos, cost: u32) {
+        let Some(i) = self.get_index(pos) else { return };
+        self.costs[i] = cost.clamp(MIN_TILE_COST, u8::MAX as u32) as u8;
+    }
+
+    pub fn clear_costs(&mut self) {
+        self.costs.fill(DEFAULT_TILE_COST as u8);
+    }
+
+    // Diagonal steps need both tiles beside them free, so routes never clip a wall's corner
+    pub fn can_step(&self, from: &TilePos, to: &TilePos, avoid: &[TilePos]) -> bool {
+        let blocked = |pos: &TilePos| self.is_solid(pos) || avoid.contains(pos);
+        if blocked(to) {
+            return false;
+        }
+        if from.x == to.x || from.y == to.y {
+            return true;
+        }
+        !blocked(&TilePos::new(to.x, from.y)) && !blocked(&TilePos::new(from.x, to.y))
+    }
+
+    fn set_solid(&mut self, pos: &TilePos, solid: bool) {
+        let Some(i) = self.get_index(pos) else { return };
+        if solid {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    // Returns false if the entity already covered exactly these tiles
+    pub fn set_entity(&mut self, entity: Entity, tiles: Vec<TilePos>) -> bool {
+        if self.entity_tiles.get(&entity) == Some(&tiles) {
+            return false;
+        }
+        self.remove_entity(entity);
+        for tile in tiles.iter() {
+            self.set_solid(tile, true);
+        }
+        self.entity_tiles.insert(entity, tiles);
+        true
+    }
+
+    pub fn remove_entity(&mut self, entity: Entity) -> bool {
+        let Some(tiles) = self.entity_tiles.remove(&entity) else { return false };
+        for tile in tiles.iter() {
+            // Another entity can still cover the tile
+            let covered = self.entity_tiles.values().any(|other| other.contains(tile));
+            if !covered {
+                self.set_solid(tile, false);
+            }
+        }
+        true
+    }
+}
+
+// Paths between tiles, including unreachable ones, until the CollisionGrid changes
+#[derive(Resource, Default)]
+pub struct PathCache {
+    paths: HashMap<(TilePos, TilePos), Option<Vec<TilePos>>>,
+}
+impl PathCache {
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    pub fn get_path(&mut self, start: TilePos, goal: TilePos, grid: &CollisionGrid) -> Option<Vec<TilePos>> {
+        if let Some(path) = self.paths.get(&(start, goal)) {
+            return path.clone();
+        }
+        if self.paths.len() >= PATH_CACHE_LIMIT {
+            self.paths.clear();
+        }
+        let path = find_tile_path(start, goal, grid, &[], None);
+        self.paths.insert((start, goal), path.clone());
+        path
+    }
+}
+
+// Extra cost of tiles crowded with workers, refreshed every second.
+// Kept out of CollisionGrid so it doesn't invalidate the PathCache every time a worker moves
+#[derive(Resource, Default)]
+pub struct PathCongestion {
+    pub tiles: HashMap<TilePos, u32>,
+}
+
+// Cost of a step between two neighbouring tiles, paid at the tile being entered
+fn get_step_cost(from: &TilePos, to: &TilePos, grid: &CollisionGrid, congestion: Option<&PathCongestion>) -> u32 {
+    let step = if from.x != to.x && from.y != to.y { DIAGONAL_STEP_COST } else { STRAIGHT_STEP_COST };
+    let crowd = congestion.and_then(|congestion| congestion.tiles.get(to)).copied().unwrap_or(0);
+    step * (grid.get_cost(to) + crowd)
+}
+
+// Octile distance at the cheapest tile cost, so it never overestimates
+fn get_distance_estimate(pos: &TilePos, goal: &TilePos) -> u32 {
+    let dx = pos.x.abs_diff(goal.x);
+    let dy = pos.y.abs_diff(goal.y);
+    let (long, short) = (dx.max(dy), dx.min(dy));
+    (STRAIGHT_STEP_COST * (long - short) + DIAGONAL_STEP_COST * short) * MIN_TILE_COST
+}
+
+// Tiles in avoid are treated as solid, used to route around other workers
+pub fn find_tile_path(
+    start: TilePos,
+    goal: TilePos,
+    grid: &CollisionGrid,
+    avoid: &[TilePos],
+    congestion: Option<&PathCongestion>,
+) -> Option<Vec<TilePos>> {
+    let map_size = grid.size;
+    let successors = |pos: &TilePos| {
+        Neighbors::get_square_neighboring_positions(pos, &map_size, true)
+            .iter()
+            .filter(|p| grid.can_step(pos, p, avoid))
+            .map(|p| (*p, get_step_cost(pos, p, grid, congestion)))
+            .collect::<Vec<_>>()
+    };
+    astar(&start, successors, |pos| get_distance_estimate(pos, &goal), |pos| is_near_tile(*pos, goal, &map_size))
+        .map(|(path, _)| path)
+}
+
+pub fn update_collision_grid(
+    mut grid: ResMut<CollisionGrid>,
+    mut path_cache: ResMut<PathCache>,
+    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &Transform)>,
+    q_solid: Query<(Entity, &Transform, Option<&EntityTileSize>), (With<SolidEntity>, Or<(Added<SolidEntity>, Changed<Transform>)>)>,
+    mut removed_solids: RemovedComponents<SolidEntity>,
+) {
+    let mut changed = false;
+    for entity in removed_solids.read() {
+        changed |= grid.remove_entity(entity);
+    }
+    if let Ok((map_size, grid_size, map_type, map_transform)) = q_tilemap.get_single() {
+        for (entity, transform, tile_size) in q_solid.iter() {
+            let tiles = get_entity_tiles(transform, tile_size, map_size, grid_size, map_type, map_transform);
+            changed |= grid.set_entity(entity, tiles);
+        }
+    }
+    if changed {
+        path_cache.clear();
+    }
+}
+
+// Blocked workers steer around crowds when they repath, cached routes ignore them
+pub fn update_path_congestion(
+    mut path_congestion: ResMut<PathCongestion>,
+    q_workers: Query<&Transform, With<MoveToTile>>,
+    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &Transform), Without<MoveToTile>>,
+) {
+    let Ok((map_size, grid_size, map_type, map_transform)) = q_tilemap.get_single() else { return };
+    let mut congestion: HashMap<TilePos, u32> = HashMap::new();
+    for transform in q_workers.iter() {
+        let world_pos = get_world_pos(transform.translation.xy(), map_transform);
+        let Some(tile_pos) = TilePos::from_world_pos(&world_pos, map_size, grid_size, map_type) else { continue };
+        *congestion.entry(tile_pos).or_insert(0) += CONGESTION_COST;
+    }
+    if path_congestion.tiles != congestion {
+        path_congestion.tiles = congestion;
+    }
+}
+
+// Waiting this long for another worker to move out of the way finds a new route
+const REPATH_SECONDS: f32 = 1.5;
+const STUCK_WARNING_SECONDS: f32 = 4.0;
+const STUCK_WARNING: &str = "Stuck in traffic";
+
 #[derive(Component, Reflect)]
 pub struct MoveToTile {
     pub target: Option<TilePos>,
     pub path: Option<Vec<TilePos>>,
-    pub path_i: usize
+    pub path_i: usize,
+    // Seconds spent waiting for the next tile to clear
+    pub blocked_time: f32,
+    // Seconds since the last repath while blocked
+    pub wait_time: f32,
+    // Walking a one tile path out of another worker's way, a new route is planned once it's reached
+    pub side_stepped: bool
 }
 
 pub fn move_towards_path(
-    mut q_move: Query<(&MoveToTile, &mut Movement, &Transform)>,
-    q_tilemap: Query<(&Transform, &TilemapGridSize, &TilemapType)>
+    time: Res<Time>,
+    grid: Res<CollisionGrid>,
+    path_congestion: Res<PathCongestion>,
+    mut q_move: Query<(Entity, &mut MoveToTile, &mut Movement, &Transform, Option<&mut JobError>)>,
+    q_player: Query<(Entity, &Transform), (With<Player>, Without<MoveToTile>)>,
+    q_tilemap: Query<(&TilemapSize, &Transform, &TilemapGridSize, &TilemapType), (Without<MoveToTile>, Without<Player>)>
 ) {
-    let (map_transform, grid_size, map_type) = q_tilemap.single();
-    for (move_to_tile, mut movement, transform) in q_move.iter_mut() {
-        if let (Some(path), Some(target)) = (&move_to_tile.path, move_to_tile.target) {
-            if move_to_tile.path_i >= path.len() {
-                movement.input = None;
-                continue;
-            }
-            let point: Vec2 = get_tile_world_pos(&path[move_to_tile.path_i], map_transform, grid_size, map_type);
-            let direction = Vec2::new(point.x as f32, point.y as f32) - Vec2::new(transform.translation.x, transform.translation.y);
-            movement.input = Some(direction.normalize());
-        } else {
+    let (map_size, map_transform, grid_size, map_type) = q_tilemap.single();
+    let get_tile = |transform: &Transform| TilePos::from_world_pos(&get_world_pos(transform.translation.xy(), map_transform), map_size, grid_size, map_type);
+
+    // Every worker and the player holds the tile they stand on, moving workers also claim their next tile
+    let mut occupied: HashMap<TilePos, Entity> = HashMap::new();
+    let mut next_tiles: HashMap<Entity, TilePos> = HashMap::new();
+    for (entity, move_to_tile, _, transform, _) in q_move.iter() {
+        let Some(tile_pos) = get_tile(transform) else { continue };
+        occupied.insert(tile_pos, entity);
+        if let Some(next) = move_to_tile.path.as_ref().and_then(|path| path.get(move_to_tile.path_i)) {
+            next_tiles.insert(entity, *next);
+        }
+    }
+    for (entity, transform) in q_player.iter() {
+        let Some(tile_pos) = get_tile(transform) else { continue };
+        occupied.insert(tile_pos, entity);
+    }
+
+    for (entity, mut move_to_tile, mut movement, transform, mut job_error) in q_move.iter_mut() {
+        let (Some(path), Some(target)) = (&move_to_tile.path, move_to_tile.target) else {
+            movement.input = None;
+            move_to_tile.blocked_time = 0.0;
+            continue;
+        };
+        if move_to_tile.path_i >= path.len() {
             movement.input = None;
+            continue;
+        }
+        let next_tile = path[move_to_tile.path_i];
+        let current_tile = get_tile(transform);
+        let blocker = occupied.get(&next_tile).copied()
+            .filter(|blocker| *blocker != entity && current_tile != Some(next_tile));
+
+        let Some(blocker) = blocker else {
+            occupied.insert(next_tile, entity);
+            let point: Vec2 = get_tile_world_pos(&next_tile, map_transform, grid_size, map_type);
+            let direction = point - transform.translation.xy();
+            movement.input = Some(direction.normalize());
+            move_to_tile.blocked_time = 0.0;
+            move_to_tile.wait_time = 0.0;
+            if let Some(job_error) = job_error.as_mut() {
+                if job_error.warning && job_error.warn_message == STUCK_WARNING {
+                    job_error.warning = false;
+                }
+            }
+            continue;
+        };
+
+        movement.input = None;
+        move_to_tile.blocked_time += time.delta_seconds();
+        move_to_tile.wait_time += time.delta_seconds();
+        if move_to_tile.blocked_time >= STUCK_WARNING_SECONDS {
+            if let Some(job_error) = job_error.as_mut() {
+                job_error.set_warning(STUCK_WARNING);
+            }
+        }
+
+        let Some(current_tile) = current_tile else { continue };
+        // Two workers walking into each other, the newer one gives way straight away
+        let deadlock = next_tiles.get(&blocker) == Some(&current_tile) && entity > blocker;
+        if !deadlock && move_to_tile.wait_time < REPATH_SECONDS {
+            continue;
+        }
+        move_to_tile.wait_time = 0.0;
+
+        let blocked_tiles = [next_tile];
+        if let Some(new_path) = find_tile_path(current_tile, target, &grid, &blocked_tiles, Some(&path_congestion)) {
+            move_to_tile.path = Some(new_path);
+            move_to_tile.path_i = 0;
+            move_to_tile.side_stepped = false;
+        } else if deadlock {
+            // No way around in a narrow corridor, so step aside and let the other worker through
+            let blocker_next = next_tiles.get(&blocker).copied();
+            let side_step = Neighbors::get_square_neighboring_positions(&current_tile, map_size, true)
+                .iter()
+                .copied()
+                .find(|tile| {
+                    grid.can_step(&current_tile, tile, &[])
+                        && !occupied.contains_key(tile)
+                        && Some(*tile) != blocker_next
+                        && *tile != next_tile
+                });
+            if let Some(side_step) = side_step {
+                occupied.insert(side_step, entity);
+                move_to_tile.path = Some(vec![side_step]);
+                move_to_tile.path_i = 0;
+                move_to_tile.side_stepped = true;
+            }
         }
     }
 }
@@ -44,22 +350,37 @@ pub fn iterate_path(
         ), move_to_tile.target, &move_to_tile.path) {
             if Some(&tile_pos) == path.get(move_to_tile.path_i) && path.len() > move_to_tile.path_i + 1 {
                 move_to_tile.path_i += 1;
-            } else if tile_pos == target {
+            } else if tile_pos == target || (move_to_tile.side_stepped && Some(&tile_pos) == path.last()) {
+                // Ending somewhere else than the target after stepping aside plans a new route
                 move_to_tile.path = None;
                 move_to_tile.path_i = 0;
+                move_to_tile.side_stepped = false;
             }
         }
     }
 }
 
+const UNREACHABLE_ERROR: &str = "Destination unreachable";
+
 pub fn set_path_to_tile(
-    mut q_move: Query<(&mut MoveToTile, &Transform)>,
+    mut q_move: Query<(&mut MoveToTile, &Transform, Option<&mut JobError>)>,
     q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &TilemapType, &Transform)>,
-    q_collision_tiles: Query<&TilePos, With<TileMapCollision>>,
+    grid: Res<CollisionGrid>,
+    mut path_cache: ResMut<PathCache>,
 ) {    
     let (map_size, grid_size, map_type , map_transform) = q_tilemap.single();
-    let collision_tiles = q_collision_tiles.iter().collect::<Vec<_>>();
-    for (mut move_to_tile, transform) in q_move.iter_mut() {
+    for (mut move_to_tile, transform, mut job_error) in q_move.iter_mut() {
+        // Something solid was built across the rest of the route
+        if grid.is_changed() {
+            let path_i = move_to_tile.path_i;
+            let blocked = move_to_tile.path.as_ref()
+                .is_some_and(|path| path.iter().skip(path_i).any(|tile| grid.is_solid(tile)));
+            if blocked {
+                move_to_tile.path = None;
+                move_to_tile.path_i = 0;
+            }
+        }
+
         let world_pos = get_world_pos(Vec2 { x: transform.translation.x, y: transform.translation.y }, map_transform);
         if let (Some(tile_pos), Some(target)) = (TilePos::from_world_pos(
             &Vec2 { x: world_pos.x, y: world_pos.y }, 
@@ -73,28 +394,22 @@ pub fn set_path_to_tile(
                 }
                 if let Some(move_path) = move_to_tile.path.as_mut() {
                     if !move_path.is_empty() { continue; };
-                    let successors   = |pos: &TilePos| {
-                        let neighbors = Neighbors::get_square_neighboring_positions(pos, map_size, true)
-                            .iter()
-                            .cloned()
-                            .filter(|p| {
-                                !collision_tiles.iter().any(|c| c.x == p.x && c.y == p.y)
-                            })
-                            .collect::<Vec<_>>();
-                        neighbors.into_iter().map(|p| (p.clone(), 1)).collect::<Vec<_>>()
-                    };
-                    let distance = |pos: &TilePos| {
-                        Vec2::new(pos.x as f32, pos.y as f32).distance(Vec2::new(target.x as f32, target.y as f32)) as u32
-                    };
-                    if let Some(path) = astar(
-                        &tile_pos, 
-                        successors,
-                        distance,
-                        |pos| {
-                            return is_near_tile(*pos, target, map_size);
+                    match path_cache.get_path(tile_pos, target, &grid) {
+                        Some(path) => {
+                            move_to_tile.path = Some(path);
+                            move_to_tile.path_i = 0;
+                            move_to_tile.side_stepped = false;
+                            if let Some(job_error) = job_error.as_mut() {
+                                if job_error.error && job_error.message == UNREACHABLE_ERROR {
+                                    job_error.clear_error();
+                                }
+                            }
+                        },
+                        None => {
+                            if let Some(job_error) = job_error.as_mut() {
+                                job_error.set_error(UNREACHABLE_ERROR);
+                            }
                         }
-                    ) {
-                        move_to_tile.path = Some(path.0);
                     }
                 } 
             }
@@ -244,7 +244,7 @@ impl TutorialSteps {
                 },
                 TutorialStep {
                     dialogue: "Now click on the ground to place a worker.
-                    \nEach worker costs $5 to hire, and will cost you 60¢ each day in salary.".to_string(),
+                    \nEach candidate has their own hiring price and daily wage.".to_string(),
                     action: world.register_system(
                         |
                             q_workers: Query<&Worker>,
@@ -371,7 +371,7 @@ impl TutorialSteps {
                             if *player_state.get() == PlayerState::None {
                                 increment_step_system(tut_steps, tut_state)
                             }
-                        }*
+                        }
                     ),
                 },
                 TutorialStep {
@@ -1,5 +1,15 @@
 use crate::*;
 
+// Typing into a text box shouldn't also trigger the game's key bindings
+pub fn block_keys_while_typing(
+    mut input: ResMut<Input<KeyCode>>,
+    q_text_boxes: Query<&TextBoxState>,
+) {
+    if q_text_boxes.iter().any(|text_box| text_box.focused) {
+        input.reset_all();
+    }
+}
+
 pub fn ui_setup(
     mut commands: Commands,
     assets: Res<AssetServer>,
@@ -35,11 +45,31 @@ pub fn ui_setup(
         widget_update_on_tick::<PowerMinigameHUDProps, EmptyState>,
         power_minigame_hud_render,
     );
+    widget_context.add_widget_system(
+        AssemblyRecipesHUDProps::default().get_name(),
+        widget_update_on_tick::<AssemblyRecipesHUDProps, EmptyState>,
+        assembly_recipes_hud_render,
+    );
+    widget_context.add_widget_system(
+        WorkerCandidatesHUDProps::default().get_name(),
+        widget_update_on_tick::<WorkerCandidatesHUDProps, EmptyState>,
+        worker_candidates_hud_render,
+    );
     widget_context.add_widget_system(
         ClockHUDProps::default().get_name(),
         widget_update_on_tick::<ClockHUDProps, EmptyState>,
         clock_hud_render,
     );
+    widget_context.add_widget_system(
+        SeedHUDProps::default().get_name(),
+        widget_update_on_tick::<SeedHUDProps, EmptyState>,
+        seed_hud_render,
+    );
+    widget_context.add_widget_system(
+        ContractsHUDProps::default().get_name(),
+        widget_update_on_tick::<ContractsHUDProps, EmptyState>,
+        contracts_hud_render,
+    );
     widget_context.add_widget_system(
         ImageButtonProps::default().get_name(),
         widget_update::<ImageButtonProps, ImageButtonState>,
@@ -78,6 +108,26 @@ pub fn ui_setup(
         widget_update_with_day_state::<ImportsSelection, EmptyState>,
         imports_selection_render,
     );
+    widget_context.add_widget_system(
+        ExportRules::default().get_name(),
+        widget_update_on_tick::<ExportRules, EmptyState>,
+        export_rules_render,
+    );
+    widget_context.add_widget_system(
+        PriceHistoryChart::default().get_name(),
+        widget_update_on_tick::<PriceHistoryChart, EmptyState>,
+        price_history_chart_render,
+    );
+    widget_context.add_widget_system(
+        HiringBoard::default().get_name(),
+        widget_update_on_tick::<HiringBoard, EmptyState>,
+        hiring_board_render,
+    );
+    widget_context.add_widget_system(
+        ContractBoard::default().get_name(),
+        widget_update_on_tick::<ContractBoard, EmptyState>,
+        contract_board_render,
+    );
     widget_context.add_widget_system(
         ImportSelector::default().get_name(),
         widget_update_with_import_selection::<ImportSelector, EmptyState>,
@@ -117,6 +117,30 @@ pub fn get_corner_tile_pos(
     return pos + Vec2::new((((size.x as f32) / 2.0) - 0.5) * TILE_SIZE.x, (((size.y as f32) / 2.0) - 0.5) * TILE_SIZE.y);
 }
 
+// Tiles covered by an entity's footprint, starting from its bottom left corner
+pub fn get_entity_tiles(
+    transform: &Transform,
+    tile_size: Option<&EntityTileSize>,
+    map_size: &TilemapSize,
+    grid_size: &TilemapGridSize,
+    map_type: &TilemapType,
+    map_transform: &Transform
+) -> Vec<TilePos> {
+    let size = tile_size.map_or(IVec2::new(1, 1), |size| size.0);
+    let world_pos = get_world_pos(Vec2 { x: transform.translation.x, y: transform.translation.y }, map_transform)
+        - Vec2::new((((size.x as f32) / 2.0) - 0.5) * TILE_SIZE.x, (((size.y as f32) / 2.0) - 0.5) * TILE_SIZE.y);
+
+    // TODO: Rotation
+    let Some(corner) = TilePos::from_world_pos(&world_pos, map_size, grid_size, map_type) else { return Vec::new() };
+    let mut tiles = Vec::new();
+    for x in 0..size.x as u32 {
+        for y in 0..size.y as u32 {
+            tiles.push(TilePos { x: corner.x + x, y: corner.y + y });
+        }
+    }
+    tiles
+}
+
 pub fn is_near_tile(
     point: TilePos,
     target: TilePos,
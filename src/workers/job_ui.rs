@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::reflect::Enum;
 
 use crate::*;
@@ -123,13 +125,144 @@ pub fn remove_job_point_click(
                 let Ok(mut job) = q_jobs.get_mut(selected) else { continue };
                 println!("Got event: {:?} {:?}", &marker.job_point, job.path);
             
-                job.path.retain(|x| x.id != marker.job_point.id);
+                job.remove_point(marker.job_point.id);
                 return;
             }
         }
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobStepButton {
+    Decrease,
+    Increase,
+    Up,
+    Down,
+    Duplicate,
+    Insert,
+    Filter,
+    Remove,
+}
+impl JobStepButton {
+    pub const ALL: [JobStepButton; 8] = [
+        JobStepButton::Decrease,
+        JobStepButton::Increase,
+        JobStepButton::Up,
+        JobStepButton::Down,
+        JobStepButton::Duplicate,
+        JobStepButton::Insert,
+        JobStepButton::Filter,
+        JobStepButton::Remove,
+    ];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            JobStepButton::Decrease => "-",
+            JobStepButton::Increase => "+",
+            JobStepButton::Up => "Up",
+            JobStepButton::Down => "Dn",
+            JobStepButton::Duplicate => "Dup",
+            JobStepButton::Insert => "Ins",
+            JobStepButton::Filter => "Flt",
+            JobStepButton::Remove => "X",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PickupFilterButton {
+    Mode,
+    Item,
+    Add,
+}
+impl PickupFilterButton {
+    pub const ALL: [PickupFilterButton; 3] = [
+        PickupFilterButton::Mode,
+        PickupFilterButton::Item,
+        PickupFilterButton::Add,
+    ];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            PickupFilterButton::Mode => "Mode",
+            PickupFilterButton::Item => "Item",
+            PickupFilterButton::Add => "Add",
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct EditJobStepEvent {
+    pub worker: Entity,
+    pub step: usize,
+    pub button: JobStepButton,
+}
+
+pub fn edit_job_steps(
+    mut ev_edit: EventReader<EditJobStepEvent>,
+    mut q_jobs: Query<&mut Job>,
+    pickup_filter: Res<SelectedPickupFilter>,
+) {
+    for ev in ev_edit.read() {
+        let Ok(mut job) = q_jobs.get_mut(ev.worker) else { continue };
+        let path_len = job.path.len();
+        let step = ev.step;
+        if step >= path_len { continue }
+
+        match ev.button {
+            JobStepButton::Decrease | JobStepButton::Increase => {
+                let change = if ev.button == JobStepButton::Increase { 1 } else { -1 };
+                let job_point = &mut job.path[step];
+                // -/+ change whatever value the step has
+                match &mut job_point.action {
+                    JobAction::ContainerPickup { pickup_amount, .. } => {
+                        *pickup_amount = (*pickup_amount + change).max(1);
+                    },
+                    JobAction::WaitForItems { amount, .. } => {
+                        *amount = (*amount as i32 + change).max(1) as usize;
+                    },
+                    JobAction::Goto { step } => {
+                        *step = (*step as i32 + change).rem_euclid(path_len as i32) as usize;
+                    },
+                    JobAction::Idle | JobAction::Zone { .. } => {
+                        let seconds = job_point.timer.as_ref().map_or(1.0, |timer| timer.duration().as_secs_f32());
+                        let seconds = (seconds + change as f32 * 0.5).max(0.5);
+                        job_point.timer = Some(Timer::new(Duration::from_secs_f32(seconds), TimerMode::Once));
+                    },
+                    _ => {}
+                }
+            },
+            JobStepButton::Up => {
+                if step == 0 { continue }
+                job.edit_path(|path| path.swap(step, step - 1));
+            },
+            JobStepButton::Down => {
+                if step + 1 >= path_len { continue }
+                job.edit_path(|path| path.swap(step, step + 1));
+            },
+            JobStepButton::Duplicate => {
+                let mut job_point = job.path[step].clone();
+                job_point.id = job.next_id();
+                job_point.job_status = JobStatus::Active;
+                job.insert_point(step + 1, job_point);
+            },
+            JobStepButton::Insert => {
+                // Clicking the map adds steps before this one until toggled off
+                job.insert_at = if job.insert_at == Some(step) { None } else { Some(step) };
+            },
+            JobStepButton::Filter => {
+                if let JobAction::ContainerPickup { filter, .. } = &mut job.path[step].action {
+                    *filter = pickup_filter.filter.clone();
+                }
+            },
+            JobStepButton::Remove => {
+                let id = job.path[step].id;
+                job.remove_point(id);
+            },
+        }
+    }
+}
+
 #[derive(Component, Clone, PartialEq, Default)]
 pub struct JobUIContainerProps;
 impl Widget for JobUIContainerProps {}
@@ -186,7 +319,9 @@ pub fn worker_menu_hud_render(
     mut query: Query<(&mut WorkerMenuHUDProps, &mut ComputedStyles, &KStyle)>,
     player_state: Res<State<PlayerState>>,
     selected_worker: Res<SelectedWorker>,
-    mut q_jobs: Query<(&mut WorkerState, &Job)>,
+    mut q_jobs: Query<(&mut WorkerState, &Job, &WorkerStats, &WorkerProfile, &WorkerExperience, &WorkerFatigue)>,
+    templates: Res<JobTemplates>,
+    pickup_filter: Res<SelectedPickupFilter>,
     time: Res<Time>,
 ) -> bool {
     if let Ok((mut props, mut computed_styles, style)) = query.get_mut(entity) {
@@ -202,7 +337,7 @@ pub fn worker_menu_hud_render(
 
             let Some(selected_worker) = selected_worker.selected else { return true };
 
-            let Ok((worker_state, job)) = q_jobs.get_mut(selected_worker) else { return true };
+            let Ok((worker_state, job, stats, profile, experience, fatigue)) = q_jobs.get_mut(selected_worker) else { return true };
 
             let current_job = job.current_job.unwrap_or_default();
             let current_job_name = job.path.get(current_job)
@@ -232,6 +367,71 @@ pub fn worker_menu_hud_render(
                             ..default()
                         }}
                     />
+                    <ElementBundle 
+                        styles={KStyle {
+                            layout_type: LayoutType::Row.into(),
+                            height: Units::Pixels(28.0).into(),
+                            ..default()
+                        }}    
+                    >
+                        <TextWidgetBundle
+                            text={TextProps {
+                                content: format!("{} - {}", profile.name, profile.get_traits_description()),
+                                ..default()
+                            }}
+                            styles={KStyle {
+                                font_size: StyleProp::Value(18.0),
+                                left: Units::Pixels(15.0).into(),
+                                ..default()
+                            }}
+                        />
+                        <ElementBundle
+                            styles={KStyle {
+                                width: Units::Pixels(48.0).into(),
+                                left: Units::Stretch(1.0).into(),
+                                right: Units::Pixels(15.0).into(),
+                                ..default()
+                            }}
+                            on_event={OnEvent::new(
+                                |
+                                    In(_entity): In<Entity>,
+                                    selected_worker: Res<SelectedWorker>,
+                                    mut ev_fire: EventWriter<FireWorkerEvent>,
+                                    event: ResMut<KEvent>,
+                                | {
+                                    if let EventType::Click(_) = event.event_type {
+                                        if let Some(worker) = selected_worker.selected {
+                                            ev_fire.send(FireWorkerEvent { worker });
+                                        }
+                                    }
+                                }
+                            )}
+                        >
+                            <TextWidgetBundle
+                                text={TextProps {
+                                    content: "Fire".to_string(),
+                                    ..default()
+                                }}
+                                styles={KStyle {
+                                    color: Color::RED.into(),
+                                    font_size: StyleProp::Value(18.0),
+                                    ..default()
+                                }}
+                            />
+                        </ElementBundle>
+                    </ElementBundle>
+                    <TextWidgetBundle
+                        text={TextProps {
+                            content: format!("Lvl {} {} Tired {:.0}%", experience.get_level(), stats.get_description(), fatigue.0 * 100.0),
+                            ..default()
+                        }}
+                        styles={KStyle {
+                            font_size: StyleProp::Value(18.0),
+                            left: Units::Pixels(15.0).into(),
+                            height: Units::Pixels(24.0).into(),
+                            ..default()
+                        }}
+                    />
                     <ElementBundle 
                         styles={KStyle {
                             layout_type: LayoutType::Row.into(),
@@ -342,22 +542,421 @@ pub fn worker_menu_hud_render(
                             )}
                         />
                     </ElementBundle>
+                    <ElementBundle
+                        styles={KStyle {
+                            layout_type: LayoutType::Row.into(),
+                            height: Units::Pixels(24.0).into(),
+                            ..default()
+                        }}
+                    >
+                        <TextWidgetBundle
+                            text={TextProps {
+                                content: "Templates:".to_string(),
+                                ..default()
+                            }}
+                            styles={KStyle {
+                                font_size: StyleProp::Value(18.0),
+                                left: Units::Pixels(15.0).into(),
+                                ..default()
+                            }}
+                        />
+                        {
+                            for button in TemplateButton::ALL {
+                                constructor!(
+                                    <ElementBundle
+                                        styles={KStyle {
+                                            width: Units::Pixels(56.0).into(),
+                                            left: Units::Pixels(10.0).into(),
+                                            ..default()
+                                        }}
+                                        on_event={OnEvent::new(
+                                            move |
+                                                In(_entity): In<Entity>,
+                                                event: ResMut<KEvent>,
+                                                selected_worker: Res<SelectedWorker>,
+                                                mut templates: ResMut<JobTemplates>,
+                                                q_workers: Query<Entity, With<Worker>>,
+                                                mut ev_save: EventWriter<SaveJobTemplateEvent>,
+                                                mut ev_apply: EventWriter<ApplyJobTemplateEvent>,
+                                            | {
+                                                if let EventType::Click(_) = event.event_type {
+                                                    let Some(worker) = selected_worker.selected else { return };
+                                                    let selected = templates.selected;
+                                                    match button {
+                                                        TemplateButton::Save => {
+                                                            ev_save.send(SaveJobTemplateEvent { worker, template: None });
+                                                        },
+                                                        TemplateButton::Update => {
+                                                            ev_save.send(SaveJobTemplateEvent { worker, template: Some(selected) });
+                                                        },
+                                                        TemplateButton::Apply => {
+                                                            ev_apply.send(ApplyJobTemplateEvent { template: selected, workers: vec![worker] });
+                                                        },
+                                                        TemplateButton::ApplyAll => {
+                                                            ev_apply.send(ApplyJobTemplateEvent { template: selected, workers: q_workers.iter().collect() });
+                                                        },
+                                                        TemplateButton::Delete => templates.remove(selected),
+                                                    }
+                                                }
+                                            }
+                                        )}
+                                    >
+                                        <TextWidgetBundle
+                                            text={TextProps {
+                                                content: button.get_name().to_string(),
+                                                ..default()
+                                            }}
+                                            styles={KStyle {
+                                                color: Color::rgb(0.1, 0.3, 0.6).into(),
+                                                font_size: StyleProp::Value(18.0),
+                                                ..default()
+                                            }}
+                                        />
+                                    </ElementBundle>
+                                );
+                            }
+                        }
+                    </ElementBundle>
+                    {
+                        for (i, template) in templates.templates.iter().enumerate() {
+                            let color = if i == templates.selected { Color::rgb(0.1, 0.5, 0.1) } else { Color::rgb(0.0, 0.0, 0.0) };
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        height: Units::Pixels(22.0).into(),
+                                        ..default()
+                                    }}
+                                    on_event={OnEvent::new(
+                                        move |
+                                            In(_entity): In<Entity>,
+                                            event: ResMut<KEvent>,
+                                            mut templates: ResMut<JobTemplates>,
+                                        | {
+                                            if let EventType::Click(_) = event.event_type {
+                                                templates.selected = i;
+                                            }
+                                        }
+                                    )}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: format!("{} ({} steps)", template.name, template.steps.len()),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            color: color.into(),
+                                            font_size: StyleProp::Value(18.0),
+                                            left: Units::Pixels(25.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                </ElementBundle>
+                            );
+                        }
+                        if let Some(template) = templates.templates.get(templates.selected) {
+                            let rename = OnChange::new(
+                                move |In((_, name)): In<(Entity, String)>, mut templates: ResMut<JobTemplates>| {
+                                    let selected = templates.selected;
+                                    if let Some(template) = templates.templates.get_mut(selected) {
+                                        template.name = name;
+                                    }
+                                }
+                            );
+                            constructor!(
+                                <ElementBundle
+                                    styles={KStyle {
+                                        layout_type: LayoutType::Row.into(),
+                                        height: Units::Pixels(30.0).into(),
+                                        ..default()
+                                    }}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: "Name:".to_string(),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            font_size: StyleProp::Value(18.0),
+                                            left: Units::Pixels(25.0).into(),
+                                            top: Units::Pixels(4.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                    <TextBoxBundle
+                                        text_box={TextBoxProps {
+                                            value: template.name.clone(),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            width: Units::Pixels(300.0).into(),
+                                            left: Units::Pixels(10.0).into(),
+                                            font_size: StyleProp::Value(18.0),
+                                            ..default()
+                                        }}
+                                        on_change={rename}
+                                    />
+                                </ElementBundle>
+                            );
+                        }
+                    }
+                    <ElementBundle
+                        styles={KStyle {
+                            layout_type: LayoutType::Row.into(),
+                            height: Units::Pixels(24.0).into(),
+                            ..default()
+                        }}
+                    >
+                        <TextWidgetBundle
+                            text={TextProps {
+                                content: format!("Pickup filter: {}", pickup_filter.filter.get_description()),
+                                ..default()
+                            }}
+                            styles={KStyle {
+                                font_size: StyleProp::Value(18.0),
+                                left: Units::Pixels(15.0).into(),
+                                ..default()
+                            }}
+                        />
+                        {
+                            for button in PickupFilterButton::ALL {
+                                constructor!(
+                                    <ElementBundle
+                                        styles={KStyle {
+                                            width: Units::Pixels(48.0).into(),
+                                            left: Units::Pixels(10.0).into(),
+                                            ..default()
+                                        }}
+                                        on_event={OnEvent::new(
+                                            move |
+                                                In(_entity): In<Entity>,
+                                                event: ResMut<KEvent>,
+                                                mut pickup_filter: ResMut<SelectedPickupFilter>,
+                                            | {
+                                                if let EventType::Click(_) = event.event_type {
+                                                    match button {
+                                                        PickupFilterButton::Mode => pickup_filter.filter.cycle_mode(),
+                                                        PickupFilterButton::Item => pickup_filter.filter.cycle_item(),
+                                                        PickupFilterButton::Add => pickup_filter.filter.add_item(),
+                                                    }
+                                                }
+                                            }
+                                        )}
+                                    >
+                                        <TextWidgetBundle
+                                            text={TextProps {
+                                                content: button.get_name().to_string(),
+                                                ..default()
+                                            }}
+                                            styles={KStyle {
+                                                color: Color::rgb(0.1, 0.3, 0.6).into(),
+                                                font_size: StyleProp::Value(18.0),
+                                                ..default()
+                                            }}
+                                        />
+                                    </ElementBundle>
+                                );
+                            }
+                        }
+                    </ElementBundle>
                     {
-                        for job_path in job.path.iter() {
+                        for (i, job_path) in job.path.iter().enumerate() {
+                            let color = match job_path.job_status {
+                                JobStatus::Skipped => Color::rgb(0.4, 0.4, 0.4),
+                                _ => Color::rgb(0.0, 0.0, 0.0),
+                            };
+                            // New steps from the map go before the marked step
+                            let marker = if job.insert_at == Some(i) { "> " } else { "" };
                             constructor!(
-                                <TextWidgetBundle
-                                    text={TextProps {
-                                        content: job_path.action.variant_name().to_owned(),
+                                <ElementBundle
+                                    styles={KStyle {
+                                        layout_type: LayoutType::Row.into(),
+                                        height: Units::Pixels(26.0).into(),
                                         ..default()
                                     }}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: format!("{}{}. {}", marker, i + 1, job_path.get_description()),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            color: color.into(),
+                                            font_size: StyleProp::Value(20.0),
+                                            left: Units::Pixels(15.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                    {
+                                        for button in JobStepButton::ALL {
+                                            constructor!(
+                                                <ElementBundle
+                                                    styles={KStyle {
+                                                        width: Units::Pixels(28.0).into(),
+                                                        left: Units::Pixels(4.0).into(),
+                                                        ..default()
+                                                    }}
+                                                    on_event={OnEvent::new(
+                                                        move |
+                                                            In(_entity): In<Entity>,
+                                                            event: ResMut<KEvent>,
+                                                            selected_worker: Res<SelectedWorker>,
+                                                            mut ev_edit: EventWriter<EditJobStepEvent>,
+                                                        | {
+                                                            if let EventType::Click(_) = event.event_type {
+                                                                let Some(worker) = selected_worker.selected else { return };
+                                                                ev_edit.send(EditJobStepEvent { worker, step: i, button });
+                                                            }
+                                                        }
+                                                    )}
+                                                >
+                                                    <TextWidgetBundle
+                                                        text={TextProps {
+                                                            content: button.get_name().to_string(),
+                                                            ..default()
+                                                        }}
+                                                        styles={KStyle {
+                                                            color: Color::rgb(0.1, 0.3, 0.6).into(),
+                                                            font_size: StyleProp::Value(18.0),
+                                                            ..default()
+                                                        }}
+                                                    />
+                                                </ElementBundle>
+                                            );
+                                        }
+                                    }
+                                </ElementBundle>
+                            );
+                        }
+                    }
+                </NinePatchBundle>
+            );
+        }
+    }
+    true
+}
+
+#[derive(Component, Clone, PartialEq, Default)]
+pub struct WorkerCandidatesHUDProps;
+impl Widget for WorkerCandidatesHUDProps {}
+
+#[derive(Bundle)]
+pub struct WorkerCandidatesHUDBundle {
+    pub props: WorkerCandidatesHUDProps,
+    pub styles: KStyle,
+    pub computed_styles: ComputedStyles,
+    pub widget_name: WidgetName,
+}
+impl Default for WorkerCandidatesHUDBundle {
+    fn default() -> Self {
+        Self {
+            props: Default::default(),
+            styles: KStyle {
+                ..default()
+            },
+            computed_styles: Default::default(),
+            widget_name: WorkerCandidatesHUDProps::default().get_name(),
+        }
+    }
+}
+
+// Picks which candidate is hired by the next worker placement
+pub fn worker_candidates_hud_render(
+    In(entity): In<Entity>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    widget_context: Res<KayakWidgetContext>,
+    mut query: Query<(&mut WorkerCandidatesHUDProps, &mut ComputedStyles, &KStyle)>,
+    player_state: Res<State<PlayerState>>,
+    candidates: Res<WorkerCandidates>,
+) -> bool {
+    if let Ok((_, mut computed_styles, style)) = query.get_mut(entity) {
+        *computed_styles = KStyle {
+            ..Default::default()
+        }
+        .with_style(style)
+        .into();
+        if player_state.get() == &PlayerState::Workers {
+            let parent_id = Some(entity);
+
+            let background = assets.load("Worker Menu.png");
+            rsx!(
+                <NinePatchBundle
+                    nine_patch={NinePatch {
+                        handle: background,
+                        ..default()
+                    }}
+                    on_event={
+                        OnEvent::new(
+                            move |In(_entity): In<Entity>, event: ResMut<KEvent>, mut placement_state: ResMut<NextState<PlacementState>> | {
+                                if let EventType::Hover(_) = event.event_type {
+                                    placement_state.set(PlacementState::Blocked);
+                                }
+                                if let EventType::MouseOut(_) = event.event_type {
+                                    placement_state.set(PlacementState::Allowed);
+                                }
+                            }
+                        )
+                    }
+                >
+                    <TextWidgetBundle
+                        text={TextProps {
+                            content: "Candidates".to_string(),
+                            ..default()
+                        }}
+                        styles={KStyle {
+                            top: Units::Pixels(15.0).into(),
+                            left: Units::Stretch(1.0).into(),
+                            right: Units::Stretch(1.0).into(),
+                            ..default()
+                        }}
+                    />
+                    {
+                        for (i, candidate) in candidates.candidates.iter().enumerate() {
+                            let color = if i == candidates.selected { Color::rgb(0.1, 0.5, 0.1) } else { Color::rgb(0.0, 0.0, 0.0) };
+                            constructor!(
+                                <ElementBundle
                                     styles={KStyle {
-                                        color: Color::rgb(0.0, 0.0, 0.0).into(),
-                                        font_size: StyleProp::Value(22.0),
-                                        left: StyleProp::Value(Units::Stretch(1.0)),
-                                        right: StyleProp::Value(Units::Stretch(1.0)),
+                                        height: Units::Pixels(44.0).into(),
+                                        top: Units::Pixels(10.0).into(),
                                         ..default()
                                     }}
-                                />
+                                    on_event={OnEvent::new(
+                                        move |
+                                            In(_entity): In<Entity>,
+                                            event: ResMut<KEvent>,
+                                            mut candidates: ResMut<WorkerCandidates>,
+                                        | {
+                                            if let EventType::Click(_) = event.event_type {
+                                                candidates.selected = i;
+                                            }
+                                        }
+                                    )}
+                                >
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: format!("{} - {}", candidate.profile.name, candidate.stats.get_description()),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            color: color.into(),
+                                            font_size: StyleProp::Value(18.0),
+                                            left: Units::Pixels(15.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                    <TextWidgetBundle
+                                        text={TextProps {
+                                            content: format!("${:.2} + ${:.2}/day", candidate.get_hire_price(), candidate.stats.wage),
+                                            ..default()
+                                        }}
+                                        styles={KStyle {
+                                            color: color.into(),
+                                            font_size: StyleProp::Value(18.0),
+                                            left: Units::Pixels(15.0).into(),
+                                            ..default()
+                                        }}
+                                    />
+                                </ElementBundle>
                             );
                         }
                     }
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use bevy::reflect::Enum;
+
 use crate::*;
 use bevy_ecs_tilemap::helpers::transform;
 use bevy_inspector_egui::{inspector_options::ReflectInspectorOptions, InspectorOptions};
@@ -7,8 +9,8 @@ use workers::worker::*;
 
 #[derive(Debug, Reflect, PartialEq, Clone)]
 pub enum JobAction {
+    // Power comes from the worker's PowerProduction when the step runs
     Work {
-        power: Power,
         assembly: Entity
     },
     Pickup {
@@ -17,39 +19,245 @@ pub enum JobAction {
     ContainerPickup {
         container: Entity,
         pickup_amount: i32,
+        filter: ItemFilter,
     },
     Drop {
         worker: Entity,
         input_container: Option<Entity>
     },
+    // Waits at the container until it holds at least this many items
+    WaitForItems {
+        container: Entity,
+        amount: usize,
+    },
+    // Jumps to another step without walking anywhere
+    Goto {
+        step: usize,
+    },
+    // Pauses the worker while the container can't take any more items
+    StopIfFull {
+        container: Entity,
+    },
+    // Walks to the nearest tile of a painted zone
+    Zone {
+        zone: ZoneType,
+    },
     Idle
 }
 
+// Which items a pickup step is allowed to take from a mixed container
+#[derive(Debug, Reflect, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub enum ItemFilter {
+    #[default]
+    Any,
+    Only(Item),
+    AnyExcept(Vec<Item>),
+}
+impl ItemFilter {
+    pub fn matches(&self, item: &Item) -> bool {
+        match self {
+            ItemFilter::Any => true,
+            ItemFilter::Only(only) => only == item,
+            ItemFilter::AnyExcept(except) => !except.contains(item),
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            ItemFilter::Any => "Any".to_string(),
+            ItemFilter::Only(item) => format!("Only {}", item.get_name()),
+            ItemFilter::AnyExcept(except) => format!(
+                "Any except {}",
+                except.iter().map(|item| item.get_name()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    // Any -> Only -> Any except -> Any
+    pub fn cycle_mode(&mut self) {
+        *self = match self {
+            ItemFilter::Any => ItemFilter::Only(Item::ALL[0]),
+            ItemFilter::Only(item) => ItemFilter::AnyExcept(vec![*item]),
+            ItemFilter::AnyExcept(_) => ItemFilter::Any,
+        };
+    }
+
+    // Changes the only item, or the last item of the except list
+    pub fn cycle_item(&mut self) {
+        match self {
+            ItemFilter::Any => {},
+            ItemFilter::Only(item) => *item = get_next_item(*item, &[]),
+            ItemFilter::AnyExcept(except) => {
+                let Some(last) = except.pop() else { return };
+                let next = get_next_item(last, except);
+                except.push(next);
+            },
+        }
+    }
+
+    pub fn add_item(&mut self) {
+        let ItemFilter::AnyExcept(except) = self else { return };
+        if let Some(item) = Item::ALL.iter().find(|item| !except.contains(item)) {
+            except.push(*item);
+        }
+    }
+}
+
+fn get_next_item(current: Item, skip: &[Item]) -> Item {
+    let start = Item::ALL.iter().position(|item| *item == current).unwrap_or(0);
+    (1..=Item::ALL.len())
+        .map(|offset| Item::ALL[(start + offset) % Item::ALL.len()])
+        .find(|item| !skip.contains(item))
+        .unwrap_or(current)
+}
+
+// Filter given to new pickup steps, edited from the worker menu
+#[derive(Resource, Default)]
+pub struct SelectedPickupFilter {
+    pub filter: ItemFilter
+}
+
+// Checked before a step runs, or after a pickup for RepeatUntilFull
+#[derive(Debug, Reflect, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum JobCondition {
+    #[default]
+    Always,
+    // Skip the step when the container it takes from or the assembly it works on is empty
+    SkipIfEmpty,
+    // Keep picking up until the worker can't carry any more
+    RepeatUntilFull,
+}
+
 #[derive(Component, Debug, Reflect)]
 pub struct JobWaiting(pub bool);
 
 #[derive(Debug, Reflect, PartialEq, Clone)]
 pub struct JobPoint {
-    pub id: u8,
+    pub id: u32,
     pub point: TilePos,
     pub point_size: IVec2,
     pub job_status: JobStatus,
     pub action: JobAction,
+    pub condition: JobCondition,
     pub timer: Option<Timer>
 }
+impl JobPoint {
+    pub fn get_description(&self) -> String {
+        let action = match &self.action {
+            JobAction::WaitForItems { amount, .. } => format!("Wait for {} items", amount),
+            JobAction::Goto { step } => format!("Goto step {}", step + 1),
+            JobAction::StopIfFull { .. } => "Stop if full".to_string(),
+            JobAction::ContainerPickup { pickup_amount, filter: ItemFilter::Any, .. } => format!("ContainerPickup x{}", pickup_amount),
+            JobAction::ContainerPickup { pickup_amount, filter, .. } => format!("ContainerPickup x{} ({})", pickup_amount, filter.get_description()),
+            JobAction::Zone { zone } => format!("Go to {} zone", zone.get_name()),
+            JobAction::Idle => match &self.timer {
+                Some(timer) => format!("Idle {:.1}s", timer.duration().as_secs_f32()),
+                None => "Idle".to_string(),
+            },
+            action => action.variant_name().to_string(),
+        };
+        match self.condition {
+            JobCondition::Always => action,
+            JobCondition::SkipIfEmpty => format!("{} (skip if empty)", action),
+            JobCondition::RepeatUntilFull => format!("{} (until full)", action),
+        }
+    }
+}
 
 #[derive(Component, Debug, Reflect)]
 pub struct Job {
     pub path: Vec<JobPoint>,
     pub complexity: f32,
     pub current_job: Option<usize>,
-    pub lock: bool
+    pub lock: bool,
+    // Items moved by the current step so far
+    pub step_items: usize,
+    // New steps are inserted before this step instead of appended
+    pub insert_at: Option<usize>
+}
+impl Job {
+    // Lowest id not used by any step, ids can't come from the path length once steps are removed.
+    // One of the first path.len() + 1 ids is always free
+    pub fn next_id(&self) -> u32 {
+        let len = self.path.len() as u32;
+        (0..=len)
+            .find(|id| !self.path.iter().any(|job_point| job_point.id == *id))
+            .unwrap_or(len)
+    }
+
+    pub fn add_point(&mut self, mut job_point: JobPoint) {
+        job_point.id = self.next_id();
+        match self.insert_at {
+            Some(index) if index < self.path.len() => {
+                self.insert_point(index, job_point);
+                self.insert_at = Some(index + 1);
+            },
+            _ => self.path.push(job_point),
+        }
+    }
+
+    // A new Goto isn't known to edit_path yet, so its target is kept by id here
+    pub fn insert_point(&mut self, index: usize, job_point: JobPoint) {
+        let id = job_point.id;
+        let target = match job_point.action {
+            JobAction::Goto { step } => self.path.get(step).map(|target| target.id),
+            _ => None,
+        };
+        self.edit_path(|path| path.insert(index, job_point));
+
+        let Some(target) = target else { return };
+        let Some(target_step) = self.path.iter().position(|job_point| job_point.id == target) else { return };
+        if let Some(JobPoint { action: JobAction::Goto { step }, .. }) = self.path.iter_mut().find(|job_point| job_point.id == id) {
+            *step = target_step;
+        }
+    }
+
+    // Goto steps keep pointing at the same step when the path is reordered
+    pub fn edit_path(&mut self, edit: impl FnOnce(&mut Vec<JobPoint>)) {
+        let mut goto_targets = Vec::new();
+        for job_point in self.path.iter() {
+            let JobAction::Goto { step } = job_point.action else { continue };
+            let Some(target) = self.path.get(step) else { continue };
+            goto_targets.push((job_point.id, target.id));
+        }
+
+        edit(&mut self.path);
+
+        let ids = self.path.iter().map(|job_point| job_point.id).collect::<Vec<_>>();
+        let last = ids.len().saturating_sub(1);
+        for job_point in self.path.iter_mut() {
+            let JobAction::Goto { step } = &mut job_point.action else { continue };
+            let target = goto_targets.iter()
+                .find(|(goto_id, _)| *goto_id == job_point.id)
+                .and_then(|(_, target_id)| ids.iter().position(|id| id == target_id));
+            *step = target.unwrap_or((*step).min(last));
+        }
+        self.insert_at = self.insert_at.filter(|index| *index < self.path.len());
+    }
+
+    pub fn remove_point(&mut self, id: u32) {
+        self.edit_path(|path| path.retain(|job_point| job_point.id != id));
+    }
+
+    // Steps before the target are skipped, everything from it onwards runs again
+    pub fn goto(&mut self, step: usize) {
+        for (i, job_point) in self.path.iter_mut().enumerate() {
+            job_point.job_status = if i < step { JobStatus::Skipped } else { JobStatus::Active };
+        }
+    }
+}
+
+// Where a Goto to `step` points once only the steps at the `kept` indexes are left,
+// a dropped target is replaced by the step that followed it
+pub fn get_kept_step(kept: &[usize], step: usize) -> usize {
+    kept.iter().position(|i| *i >= step).unwrap_or(kept.len().saturating_sub(1))
 }
 
 #[derive(Component, Debug, PartialEq, Reflect, Clone)]
 pub enum JobStatus {
     Active,
-    Completed
+    Completed,
+    Skipped
 }
 
 #[derive(Resource)]
@@ -88,14 +296,43 @@ pub fn job_mode_creation(
     q_assembly_output: Query<(&ContainerOutputSelector, &Parent, &GlobalTransform)>,
     q_items: Query<Entity, With<Item>>,
     mouse_input: Res<Input<MouseButton>>,
+    key_input: Res<Input<KeyCode>>,
     mouse_pos: Res<MouseTile>,
     selected_worker: Res<SelectedWorker>,
-    mut q_worker: Query<(&mut Job, &PowerProduction), With<Worker>>,
+    pickup_filter: Res<SelectedPickupFilter>,
+    floor_zones: Res<FloorZones>,
+    mut q_worker: Query<(&mut Job, &ItemContainer), With<Worker>>,
     q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &Transform, &TilemapType)>
 ) {
     let (tilemap_size, grid_size, map_transform, map_type) = q_tilemap.get_single().unwrap();
     let Some(worker_entity) = selected_worker.selected else { return; };
-    let Ok((mut job, power_production)) = q_worker.get_mut(worker_entity) else { return; };
+    let Ok((mut job, worker_items)) = q_worker.get_mut(worker_entity) else { return; };
+    // Shift repeats pickups until full, Ctrl skips the step when empty, Alt waits or stops on the container
+    let shift = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let ctrl = key_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let alt = key_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let condition = if shift {
+        JobCondition::RepeatUntilFull
+    } else if ctrl {
+        JobCondition::SkipIfEmpty
+    } else {
+        JobCondition::Always
+    };
+
+    if key_input.just_pressed(KeyCode::G) {
+        let point = job.path.last().map_or(mouse_pos.0, |job_point| job_point.point);
+        let job_point = JobPoint {
+            id: 0,
+            point,
+            point_size: IVec2::ONE,
+            job_status: JobStatus::Active,
+            action: JobAction::Goto { step: 0 },
+            condition: JobCondition::Always,
+            timer: None
+        };
+        job.add_point(job_point);
+        return;
+    }
     for ev in mouse_collision.read() {
         if mouse_input.just_pressed(MouseButton::Left) {
             if let Some((_, entity)) = ev.collision {
@@ -104,49 +341,68 @@ pub fn job_mode_creation(
                     let assembly_pos = get_corner_tile_pos(assembly_world_pos, tile_size.0);
                     if let Some(assembly_tile_pos) = TilePos::from_world_pos(&assembly_pos, tilemap_size, grid_size, map_type) {
                         let action: JobAction = JobAction::Work {
-                            power: power_production.power,
                             assembly,
                         };
                         let job_point = JobPoint {
-                            id: job.path.len() as u8,
+                            id: 0,
                             point: assembly_tile_pos,
                             job_status: JobStatus::Active,
                             point_size: tile_size.0,
                             action,
+                            condition: if ctrl { JobCondition::SkipIfEmpty } else { JobCondition::Always },
                             timer: None
                         };
-                        job.path.push(job_point);
+                        job.add_point(job_point);
                         return;
                     }
                 }
                 if let Ok((assembly_input, parent, transform)) = q_assembly_input.get(entity) {
+                    let action = if alt {
+                        JobAction::StopIfFull {
+                            container: parent.get()
+                        }
+                    } else {
+                        JobAction::Drop {
+                            input_container: Some(parent.get()),
+                            worker: worker_entity
+                        }
+                    };
                     let job_point = JobPoint {
-                        id: job.path.len() as u8,
+                        id: 0,
                         point: TilePos::from_world_pos(&get_world_pos(transform.translation().xy(), map_transform), tilemap_size, grid_size, map_type).unwrap(),
                         point_size: IVec2::ONE,
                         job_status: JobStatus::Active,
-                        action: JobAction::Drop {
-                            input_container: Some(parent.get()),
-                            worker: worker_entity
-                        },
+                        action,
+                        condition: JobCondition::Always,
                         timer: None
                     };
-                    job.path.push(job_point);
+                    job.add_point(job_point);
                     return;
                 }
                 if let Ok((assembly_output, parent, transform)) = q_assembly_output.get(entity) {
+                    let action = if alt {
+                        // Wait for a full load so the trip isn't wasted
+                        JobAction::WaitForItems {
+                            container: parent.get(),
+                            amount: worker_items.max_items
+                        }
+                    } else {
+                        JobAction::ContainerPickup {
+                            container: parent.get(),
+                            pickup_amount: 1,
+                            filter: pickup_filter.filter.clone()
+                        }
+                    };
                     let job_point = JobPoint {
-                        id: job.path.len() as u8,
+                        id: 0,
                         point: TilePos::from_world_pos(&get_world_pos(transform.translation().xy(), map_transform), tilemap_size, grid_size, map_type).unwrap(),
                         point_size: IVec2::ONE,
                         job_status: JobStatus::Active,
-                        action: JobAction::ContainerPickup {
-                            container: parent.get(),
-                            pickup_amount: 1
-                        },
+                        action,
+                        condition,
                         timer: None
                     };
-                    job.path.push(job_point);
+                    job.add_point(job_point);
                     return;
                 }
                 // if let Ok(item) = q_items.get(entity) {
@@ -160,7 +416,7 @@ pub fn job_mode_creation(
                 //         action,
                 //         timer: None
                 //     };
-                //     job.path.push(job_point);
+                //     job.add_point(job_point);
                 //     return;
                 // }
             }
@@ -168,37 +424,128 @@ pub fn job_mode_creation(
     }
     
     if mouse_input.just_pressed(MouseButton::Left) {
+        let action = match floor_zones.zones.get(&mouse_pos.0) {
+            Some(zone) => JobAction::Zone { zone: *zone },
+            None => JobAction::Idle,
+        };
         let job_point = JobPoint {
-            id: job.path.len() as u8,
+            id: 0,
             point: mouse_pos.0,
             point_size: IVec2::ONE,
             job_status: JobStatus::Active,
-            action: JobAction::Idle,
+            action,
+            condition: JobCondition::Always,
             timer: Some(Timer::new(Duration::from_secs_f32(1.0), TimerMode::Once))
         };
-        job.path.push(job_point);
+        job.add_point(job_point);
+    }
+}
+
+// Items a step can take from or work on, None if the step has no container
+fn get_job_item_count(
+    action: &JobAction,
+    q_item_containers: &Query<&ItemContainer, Without<Worker>>,
+    q_assembly_containers: &Query<&ItemIOContainer>,
+) -> Option<usize> {
+    match action {
+        JobAction::Work { assembly } => q_assembly_containers.get(*assembly).ok().map(|c| c.input.items.len()),
+        JobAction::ContainerPickup { container, .. } | JobAction::WaitForItems { container, .. } => {
+            if let Ok(item_container) = q_item_containers.get(*container) {
+                return Some(item_container.items.len());
+            }
+            q_assembly_containers.get(*container).ok().map(|c| c.output.items.len())
+        },
+        _ => None
+    }
+}
+
+fn is_container_full(
+    container: Entity,
+    q_item_containers: &Query<&ItemContainer, Without<Worker>>,
+    q_assembly_containers: &Query<&ItemIOContainer>,
+) -> bool {
+    if let Ok(item_container) = q_item_containers.get(container) {
+        return item_container.items.len() >= item_container.max_items;
     }
+    q_assembly_containers.get(container).is_ok_and(|c| c.input.items.len() >= c.input.max_items)
+}
+
+// Carried stacks hold a single item type, so only items matching it and the step's filter can be picked up
+fn get_pickup_items(items: &[Option<Entity>], carried_type: Option<Item>, filter: &ItemFilter, q_items: &Query<&Item>) -> Vec<Entity> {
+    items.iter()
+        .filter_map(|item| *item)
+        .filter(|item| q_items.get(*item).is_ok_and(|item| {
+            filter.matches(item) && carried_type.is_none_or(|carried| *item == carried)
+        }))
+        .collect()
 }
 
 pub fn worker_iterate_jobs(
-    mut q_jobs: Query<(&mut Job, &mut WorkerState)>,
+    mut q_jobs: Query<(&mut Job, &mut WorkerState, &mut JobError)>,
+    q_item_containers: Query<&ItemContainer, Without<Worker>>,
+    q_assembly_containers: Query<&ItemIOContainer>,
+    floor_zones: Res<FloorZones>,
 ) {
-    for (mut job,  state) in q_jobs.iter_mut() {
+    for (mut job, mut state, mut job_error) in q_jobs.iter_mut() {
         if *state == WorkerState::Paused {
             continue;
         }
-        let active_jobs: Vec<&JobPoint> = job.path.iter().filter(|path| -> bool {
-            return path.job_status == JobStatus::Active;
-        }).collect();
-        if active_jobs.len() == 0 && job.path.len() > 0 {
-            for job_path in &mut job.path {
-                job_path.job_status = JobStatus::Active;
+        // Control flow resolves instantly, capped so a goto loop can't stall the frame
+        for _ in 0..=job.path.len() {
+            if !job.path.is_empty() && job.path.iter().all(|path| path.job_status != JobStatus::Active) {
+                for job_path in &mut job.path {
+                    job_path.job_status = JobStatus::Active;
+                }
+            }
+            let Some(current_job_i) = job.path.iter().position(|path| path.job_status == JobStatus::Active) else { break };
+            let job_point = &job.path[current_job_i];
+            match job_point.action {
+                JobAction::Goto { step } => {
+                    if step == current_job_i || step >= job.path.len() {
+                        job.path[current_job_i].job_status = JobStatus::Completed;
+                    } else {
+                        job.goto(step);
+                    }
+                    continue;
+                },
+                JobAction::StopIfFull { container } => {
+                    if is_container_full(container, &q_item_containers, &q_assembly_containers) {
+                        *state = WorkerState::Paused;
+                        job_error.set_warning("Container is full, stopped working");
+                        break;
+                    }
+                    job.path[current_job_i].job_status = JobStatus::Completed;
+                    continue;
+                },
+                // Follow the zone if it was repainted somewhere else
+                JobAction::Zone { zone } if floor_zones.zones.get(&job_point.point) != Some(&zone) => {
+                    let missing_error = format!("No {} zone", zone.get_name());
+                    match floor_zones.get_nearest(job_point.point, zone) {
+                        Some(point) => {
+                            job.path[current_job_i].point = point;
+                            if job_error.message == missing_error {
+                                job_error.clear_error();
+                            }
+                        },
+                        None => job_error.set_error(&missing_error),
+                    }
+                    break;
+                },
+                _ => {}
             }
-            job.current_job = Some(0);
-        } else {
-            let current_job_i = job.path.iter().position(|j| { j == active_jobs[0] }).unwrap_or(0);
-            job.current_job = Some(current_job_i);
+            if job_point.condition == JobCondition::SkipIfEmpty
+                && get_job_item_count(&job_point.action, &q_item_containers, &q_assembly_containers) == Some(0) {
+                job.path[current_job_i].job_status = JobStatus::Skipped;
+                continue;
+            }
+            break;
         }
+
+        let current_job_i = job.path.iter().position(|path| path.job_status == JobStatus::Active).unwrap_or(0);
+        if job.current_job != Some(current_job_i) {
+            job.step_items = 0;
+        }
+        job.current_job = Some(current_job_i);
     }
 }
 
@@ -210,22 +557,29 @@ pub struct ItemJobLock {
 
 pub fn worker_do_job(
     time: Res<Time>,
-    mut q_jobs: Query<(&mut Job, Entity, &Transform), With<Worker>>,
+    mut q_jobs: Query<(&mut Job, Entity, &Transform, &PowerProduction), With<Worker>>,
     q_tilemap: Query<(&Transform, &TilemapSize, &TilemapGridSize, &TilemapType)>,
     mut q_item_containers: Query<&mut ItemContainer>,
     mut q_assembly_containers: Query<&mut ItemIOContainer>,
+    q_items: Query<&Item>,
     mut locked_items: ResMut<ItemJobLock>,
     mut ev_assembly_power: EventWriter<AssemblyPowerInput>,
     mut ev_item_pickup: EventWriter<WorkerPickUpItemEvent>,
     mut ev_item_drop: EventWriter<WorkerDropItemEvent>
 ) {
     let (map_transform, map_size, grid_size, map_type) = q_tilemap.single();
-    for (mut job, worker_entity, transform) in q_jobs.iter_mut() {
+    for (mut job, worker_entity, transform, production) in q_jobs.iter_mut() {
         // Make sure each job is only run once per frame
         if job.lock {
             continue;
         }
         job.lock = true;
+        let worker_items = q_item_containers.get(worker_entity).ok();
+        let worker_full = worker_items.is_none_or(|items| items.items.len() >= items.max_items);
+        let carried_type = worker_items
+            .and_then(|items| items.items.first().copied().flatten())
+            .and_then(|item| q_items.get(item).ok())
+            .copied();
         let world_pos = get_world_pos(Vec2 { x: transform.translation.x, y: transform.translation.y }, map_transform);
         let tile_pos = TilePos::from_world_pos(&world_pos, map_size, grid_size, map_type);
         if let (Some(tile_pos), Some(current_job_i)) = (tile_pos, job.current_job) {
@@ -248,16 +602,31 @@ pub fn worker_do_job(
                     continue;
                 }
                 match current_job.action {
-                    JobAction::Work { power, assembly } => {
+                    JobAction::Work { assembly } => {
+                        // Power depends on the worker's current strength and fatigue
                         ev_assembly_power.send(AssemblyPowerInput {
                             assembly,
                             source: worker_entity,
-                            power,
+                            power: production.power,
                         });
                     },
-                    JobAction::Idle => {
+                    JobAction::Idle | JobAction::Zone { .. } => {
                         current_job.job_status = JobStatus::Completed;
                     },
+                    JobAction::WaitForItems { container, amount } => {
+                        let count = if let Ok(item_container) = q_item_containers.get(container) {
+                            item_container.items.len()
+                        } else if let Ok(assembly_container) = q_assembly_containers.get(container) {
+                            assembly_container.output.items.len()
+                        } else {
+                            0
+                        };
+                        if count >= amount {
+                            current_job.job_status = JobStatus::Completed;
+                        }
+                    },
+                    // Resolved in worker_iterate_jobs
+                    JobAction::Goto { .. } | JobAction::StopIfFull { .. } => {},
                     JobAction::Pickup { item } => {
                         if locked_items.items.contains(&item) {
                             continue;
@@ -271,51 +640,27 @@ pub fn worker_do_job(
                             container: None
                         });
                     },
-                    JobAction::ContainerPickup { container, pickup_amount } => {
-                        if let Ok(item_container) = q_item_containers.get_mut(container) {
-                            if item_container.items.len() == 0 {
-                                current_job.job_status = JobStatus::Completed
-                            }
-                            // TODO: Grab any available item or configurable?
-                            if let Some(Some(item)) = item_container.items.iter()
-                            .filter(|i| {
-                                if let Some(i) = i {
-                                    !locked_items.items.contains(i)
-                                } else {
-                                    false
-                                }
-                            })
-                            .last() {
-                                println!("Picking up item {:?}", item);
-                                locked_items.items.push(*item);
-                                ev_item_pickup.send(WorkerPickUpItemEvent {
-                                    item: *item,
-                                    worker: worker_entity,
-                                    tile_pos,
-                                    container: Some(container)
-                                });
-                            }
-                        } else if let Ok(assembly_container) = q_assembly_containers.get_mut(container) {
-                            if assembly_container.output.items.len() == 0 {
-                                current_job.job_status = JobStatus::Completed
-                            }
-                            if let Some(Some(item)) = assembly_container.output.items.iter()
-                            .filter(|i| {
-                                if let Some(i) = i {
-                                    !locked_items.items.contains(i)
-                                } else {
-                                    false
-                                }
-                            })
-                            .last() {
-                                locked_items.items.push(*item);
-                                ev_item_pickup.send(WorkerPickUpItemEvent {
-                                    item: *item,
-                                    worker: worker_entity,
-                                    tile_pos,
-                                    container: Some(container)
-                                });
-                            }
+                    JobAction::ContainerPickup { container, ref filter, .. } => {
+                        let source_items = if let Ok(item_container) = q_item_containers.get(container) {
+                            get_pickup_items(&item_container.items, carried_type, filter, &q_items)
+                        } else if let Ok(assembly_container) = q_assembly_containers.get(container) {
+                            get_pickup_items(&assembly_container.output.items, carried_type, filter, &q_items)
+                        } else {
+                            Vec::new()
+                        };
+                        if source_items.is_empty() || worker_full {
+                            current_job.job_status = JobStatus::Completed;
+                            job.lock = false;
+                            continue;
+                        }
+                        if let Some(item) = source_items.iter().rev().find(|item| !locked_items.items.contains(item)) {
+                            locked_items.items.push(*item);
+                            ev_item_pickup.send(WorkerPickUpItemEvent {
+                                item: *item,
+                                worker: worker_entity,
+                                tile_pos,
+                                container: Some(container)
+                            });
                         }
                     }
                     JobAction::Drop { worker: container, input_container } => {
@@ -352,7 +697,7 @@ pub fn worker_path_to_next_job(
             continue;
         }
         let active_jobs: Vec<&JobPoint> = job.path.iter().filter(|path| -> bool {
-            return path.job_status != JobStatus::Completed;
+            return path.job_status == JobStatus::Active;
         }).collect();
         if let Some(job_point) = active_jobs.first() {
             let worker_pos = Vec2 {
@@ -380,3 +725,158 @@ pub fn worker_path_to_next_job(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn job_point(id: u32, action: JobAction) -> JobPoint {
+        JobPoint {
+            id,
+            point: TilePos::new(0, 0),
+            point_size: IVec2::ONE,
+            job_status: JobStatus::Active,
+            action,
+            condition: JobCondition::Always,
+            timer: None
+        }
+    }
+
+    // Two idle steps followed by a Goto back to the second one
+    fn goto_job() -> Job {
+        Job {
+            path: vec![
+                job_point(0, JobAction::Idle),
+                job_point(1, JobAction::Idle),
+                job_point(2, JobAction::Goto { step: 1 }),
+            ],
+            complexity: 0.0,
+            current_job: None,
+            lock: false,
+            step_items: 0,
+            insert_at: None
+        }
+    }
+
+    fn get_goto_step(job: &Job) -> Option<usize> {
+        job.path.iter().find_map(|job_point| match job_point.action {
+            JobAction::Goto { step } => Some(step),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn reordering_keeps_goto_target() {
+        let mut job = goto_job();
+        job.edit_path(|path| path.swap(0, 1));
+        assert_eq!(get_goto_step(&job), Some(0));
+
+        job.edit_path(|path| path.swap(1, 2));
+        assert_eq!(get_goto_step(&job), Some(0));
+        assert_eq!(job.path[1].action, JobAction::Goto { step: 0 });
+    }
+
+    #[test]
+    fn removing_goto_target_points_at_next_step() {
+        let mut job = goto_job();
+        job.remove_point(0);
+        assert_eq!(get_goto_step(&job), Some(0));
+
+        // The step after the removed target is the Goto itself
+        job.remove_point(1);
+        assert_eq!(get_goto_step(&job), Some(0));
+        assert_eq!(job.path.len(), 1);
+    }
+
+    #[test]
+    fn inserting_before_goto_target() {
+        let mut job = goto_job();
+        let id = job.next_id();
+        job.insert_point(0, job_point(id, JobAction::Idle));
+        assert_eq!(get_goto_step(&job), Some(2));
+
+        // Inserting after the target leaves the Goto alone
+        let id = job.next_id();
+        job.insert_point(3, job_point(id, JobAction::Idle));
+        assert_eq!(get_goto_step(&job), Some(2));
+    }
+
+    #[test]
+    fn inserted_goto_keeps_its_target() {
+        let mut job = goto_job();
+        job.remove_point(2);
+        let id = job.next_id();
+        job.insert_point(0, job_point(id, JobAction::Goto { step: 1 }));
+        assert_eq!(job.path[0].action, JobAction::Goto { step: 2 });
+        assert_eq!(job.path[2].id, 1);
+    }
+
+    #[test]
+    fn added_points_get_unused_ids() {
+        let mut job = goto_job();
+        job.remove_point(1);
+        job.insert_at = Some(0);
+        job.add_point(job_point(0, JobAction::Idle));
+        job.add_point(job_point(0, JobAction::Idle));
+
+        let mut ids = job.path.iter().map(|job_point| job_point.id).collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), job.path.len());
+        assert_eq!(job.insert_at, Some(2));
+    }
+
+    #[test]
+    fn kept_step_replaces_dropped_target() {
+        let kept = [0, 2, 3];
+        assert_eq!(get_kept_step(&kept, 0), 0);
+        assert_eq!(get_kept_step(&kept, 1), 1);
+        assert_eq!(get_kept_step(&kept, 3), 2);
+        // Nothing follows a dropped last step, so the Goto falls back to the new last step
+        assert_eq!(get_kept_step(&kept, 4), 2);
+        assert_eq!(get_kept_step(&[], 2), 0);
+    }
+
+    #[test]
+    fn cycle_item_skips_excluded_items() {
+        let mut filter = ItemFilter::Only(Item::ALL[Item::ALL.len() - 1]);
+        filter.cycle_item();
+        assert_eq!(filter, ItemFilter::Only(Item::ALL[0]));
+
+        let mut filter = ItemFilter::AnyExcept(vec![Item::ALL[1], Item::ALL[0]]);
+        filter.cycle_item();
+        assert_eq!(filter, ItemFilter::AnyExcept(vec![Item::ALL[1], Item::ALL[2]]));
+
+        let mut filter = ItemFilter::Any;
+        filter.cycle_item();
+        assert_eq!(filter, ItemFilter::Any);
+    }
+
+    fn run_iterate_jobs(job: Job) -> Job {
+        let mut world = World::new();
+        world.init_resource::<FloorZones>();
+        let worker = world.spawn((job, WorkerState::Working, JobError::new())).id();
+        world.run_system_once(worker_iterate_jobs);
+        world.entity_mut(worker).take::<Job>().unwrap()
+    }
+
+    #[test]
+    fn goto_skips_to_its_target() {
+        let mut job = goto_job();
+        job.path.insert(0, job_point(3, JobAction::Goto { step: 2 }));
+        let job = run_iterate_jobs(job);
+        assert_eq!(job.current_job, Some(2));
+        assert_eq!(job.path[0].job_status, JobStatus::Skipped);
+        assert_eq!(job.path[1].job_status, JobStatus::Skipped);
+    }
+
+    #[test]
+    fn goto_loop_without_work_stops() {
+        let mut job = goto_job();
+        job.path = vec![job_point(0, JobAction::Goto { step: 1 }), job_point(1, JobAction::Goto { step: 0 })];
+        let job = run_iterate_jobs(job);
+        assert!(job.current_job.is_some());
+    }
+}
@@ -13,6 +13,12 @@ pub use job_ui::*;
 
 mod error;
 pub use error::*;
+
+mod skills;
+pub use skills::*;
+
+mod templates;
+pub use templates::*;
 pub struct WorkerPlugin;
 
 impl Plugin for WorkerPlugin {
@@ -48,12 +54,16 @@ impl Plugin for WorkerPlugin {
                     worker_pick_up_item,
                     worker_drop_item,
                     job_error_marker,
+                    (worker_gain_experience, worker_fatigue, apply_worker_stats).chain(),
                     job_warning_marker,
                     path_marker_hover,
                     (spawn_job_path_markers, job_path_lines, remove_job_point_click).run_if(in_state(PlayerState::Jobs)),
                 ).run_if(in_state(DayCycleState::Day))
             )
             .add_systems(OnExit(PlayerState::Jobs), despawn_job_path_markers)
+            .add_systems(OnEnter(DayCycleState::Night), refresh_worker_candidates.after(refresh_contract_offers))
+            .add_systems(OnEnter(DayCycleState::Day), place_hired_workers)
+            .add_systems(Update, (hire_worker, fire_worker, save_job_template, apply_job_template, edit_job_steps))
             .add_systems(PreUpdate, (
                 mouse_collision_system::<Worker>,
                 mouse_collision_system::<JobPathMarker>,
@@ -63,16 +73,30 @@ impl Plugin for WorkerPlugin {
             .add_event::<MouseCollisionEvent>()
             .add_event::<WorkerPickUpItemEvent>()
             .add_event::<WorkerDropItemEvent>()
+            .add_event::<HireWorkerEvent>()
+            .add_event::<FireWorkerEvent>()
+            .add_event::<SaveJobTemplateEvent>()
+            .add_event::<ApplyJobTemplateEvent>()
+            .add_event::<EditJobStepEvent>()
             .register_type::<Job>()
             .register_type::<JobStatus>()
+            .register_type::<JobCondition>()
+            .register_type::<ItemFilter>()
             .register_type::<WorkerState>()
             .register_type::<MoveToTile>()
             .register_type::<JobError>()
             .register_type::<PowerProduction>()
+            .register_type::<WorkerStats>()
+            .register_type::<WorkerExperience>()
+            .register_type::<WorkerFatigue>()
+            .register_type::<WorkerProfile>()
             .register_type::<ItemJobLock>()
             .insert_resource(SelectedWorker {
                 selected: None
             })
-            .insert_resource(ItemJobLock::default());
+            .insert_resource(ItemJobLock::default())
+            .init_resource::<WorkerCandidates>()
+            .init_resource::<JobTemplates>()
+            .init_resource::<SelectedPickupFilter>();
     }
 }
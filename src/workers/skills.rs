@@ -0,0 +1,354 @@
+use rand::Rng;
+
+use crate::*;
+
+const BASE_WORKER_POWER: f32 = 20.0;
+const BASE_WORKER_SPEED: f32 = 1.25;
+pub const BASE_WORKER_WAGE: f32 = 0.6;
+
+const XP_PER_JOB_POINT: f32 = 1.0;
+const XP_PER_LEVEL: f32 = 25.0;
+const MAX_WORKER_LEVEL: u32 = 10;
+// Power and speed bonus for every level past the first
+const LEVEL_BONUS: f32 = 0.05;
+
+// A full day of work wears a worker out completely
+const FATIGUE_PER_SECOND: f32 = 1.0 / DAY_LENGTH_SECONDS;
+const FATIGUE_POWER_PENALTY: f32 = 0.5;
+const FATIGUE_SPEED_PENALTY: f32 = 0.3;
+
+const CANDIDATE_COUNT: usize = 4;
+
+const FIRST_NAMES: [&str; 12] = ["Ada", "Bram", "Cora", "Dmitri", "Edie", "Finn", "Greta", "Hugo", "Iris", "Jonas", "Kit", "Lena"];
+const LAST_NAMES: [&str; 10] = ["Alder", "Birch", "Cedar", "Hawthorn", "Larch", "Maple", "Oakley", "Pine", "Rowan", "Willow"];
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerStats {
+    // Multiplies the power put into assemblies
+    pub strength: f32,
+    // Multiplies walking speed
+    pub speed: f32,
+    pub carry_capacity: usize,
+    // Multiplies how quickly the worker tires
+    pub fatigue_rate: f32,
+    // Paid every night through the UpkeepTracker
+    pub wage: f32,
+}
+impl Default for WorkerStats {
+    fn default() -> Self {
+        Self {
+            strength: 1.0,
+            speed: 1.0,
+            carry_capacity: 1,
+            fatigue_rate: 1.0,
+            wage: BASE_WORKER_WAGE,
+        }
+    }
+}
+impl WorkerStats {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let strength = rng.gen_range(0.7..1.4);
+        let speed = rng.gen_range(0.7..1.4);
+        let carry_capacity = rng.gen_range(1..=3);
+        Self {
+            strength,
+            speed,
+            carry_capacity,
+            ..default()
+        }
+    }
+
+    // Better workers ask for more
+    fn calculate_wage(&mut self) {
+        let quality = (self.strength + self.speed) / 2.0
+            + (self.carry_capacity - 1) as f32 * 0.15
+            + (1.0 - self.fatigue_rate) * 0.5;
+        self.wage = BASE_WORKER_WAGE * quality.max(0.5);
+    }
+
+    pub fn get_hire_price(&self) -> f32 {
+        WORKER_PRICE * self.wage / BASE_WORKER_WAGE
+    }
+
+    pub fn get_description(&self) -> String {
+        format!("Str {:.1} Spd {:.1} Carry {}", self.strength, self.speed, self.carry_capacity)
+    }
+}
+
+#[derive(Component, Clone, Debug, Default, Reflect)]
+pub struct WorkerExperience {
+    pub xp: f32,
+    // Job point ids already rewarded this loop of the job
+    pub completed: Vec<u32>,
+}
+impl WorkerExperience {
+    pub fn get_level(&self) -> u32 {
+        (1 + (self.xp / XP_PER_LEVEL) as u32).min(MAX_WORKER_LEVEL)
+    }
+}
+
+// 0 when rested, 1 when worn out
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+pub struct WorkerFatigue(pub f32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum WorkerTrait {
+    Strong,
+    Frail,
+    Quick,
+    Sluggish,
+    PackMule,
+    Tireless,
+    Lazy,
+}
+impl WorkerTrait {
+    const ALL: [WorkerTrait; 7] = [
+        WorkerTrait::Strong,
+        WorkerTrait::Frail,
+        WorkerTrait::Quick,
+        WorkerTrait::Sluggish,
+        WorkerTrait::PackMule,
+        WorkerTrait::Tireless,
+        WorkerTrait::Lazy,
+    ];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            WorkerTrait::Strong => "Strong",
+            WorkerTrait::Frail => "Frail",
+            WorkerTrait::Quick => "Quick",
+            WorkerTrait::Sluggish => "Sluggish",
+            WorkerTrait::PackMule => "Pack Mule",
+            WorkerTrait::Tireless => "Tireless",
+            WorkerTrait::Lazy => "Lazy",
+        }
+    }
+
+    fn apply(&self, stats: &mut WorkerStats) {
+        match self {
+            WorkerTrait::Strong => stats.strength *= 1.3,
+            WorkerTrait::Frail => stats.strength *= 0.75,
+            WorkerTrait::Quick => stats.speed *= 1.3,
+            WorkerTrait::Sluggish => stats.speed *= 0.75,
+            WorkerTrait::PackMule => stats.carry_capacity += 1,
+            WorkerTrait::Tireless => stats.fatigue_rate *= 0.5,
+            WorkerTrait::Lazy => stats.fatigue_rate *= 1.5,
+        }
+    }
+}
+
+// Who the worker is, kept after they are hired
+#[derive(Component, Clone, Debug, Default, Reflect, Serialize, Deserialize)]
+pub struct WorkerProfile {
+    pub name: String,
+    pub traits: Vec<WorkerTrait>,
+}
+impl WorkerProfile {
+    pub fn get_traits_description(&self) -> String {
+        if self.traits.is_empty() {
+            return "No traits".to_string();
+        }
+        self.traits.iter().map(|t| t.get_name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorkerCandidate {
+    pub profile: WorkerProfile,
+    pub stats: WorkerStats,
+}
+impl WorkerCandidate {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let name = format!(
+            "{} {}",
+            FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())],
+            LAST_NAMES[rng.gen_range(0..LAST_NAMES.len())]
+        );
+        let mut stats = WorkerStats::random(rng);
+        let mut traits = Vec::new();
+        for _ in 0..rng.gen_range(0..=2) {
+            let worker_trait = WorkerTrait::ALL[rng.gen_range(0..WorkerTrait::ALL.len())];
+            if traits.contains(&worker_trait) { continue }
+            worker_trait.apply(&mut stats);
+            traits.push(worker_trait);
+        }
+        stats.calculate_wage();
+        Self {
+            profile: WorkerProfile { name, traits },
+            stats,
+        }
+    }
+
+    pub fn get_hire_price(&self) -> f32 {
+        self.stats.get_hire_price()
+    }
+}
+
+// The hiring board, filled once the game's seed is known and refreshed every night
+#[derive(Resource, Default)]
+pub struct WorkerCandidates {
+    pub candidates: Vec<WorkerCandidate>,
+    // Candidate placed by clicking in worker mode
+    pub selected: usize,
+    // Hired at night, they show up when the day starts
+    pub hired: Vec<WorkerCandidate>,
+}
+impl WorkerCandidates {
+    pub fn get_selected(&self) -> Option<&WorkerCandidate> {
+        self.candidates.get(self.selected)
+    }
+
+    pub fn take(&mut self, index: usize) -> Option<WorkerCandidate> {
+        if index >= self.candidates.len() {
+            return None;
+        }
+        let candidate = self.candidates.remove(index);
+        self.selected = self.selected.min(self.candidates.len().saturating_sub(1));
+        Some(candidate)
+    }
+
+    pub fn refresh(&mut self, rng: &mut impl Rng) {
+        self.candidates = (0..CANDIDATE_COUNT).map(|_| WorkerCandidate::random(rng)).collect();
+        self.selected = 0;
+    }
+}
+
+#[derive(Event)]
+pub struct HireWorkerEvent {
+    pub candidate: usize,
+}
+
+#[derive(Event)]
+pub struct FireWorkerEvent {
+    pub worker: Entity,
+}
+
+pub fn refresh_worker_candidates(
+    mut candidates: ResMut<WorkerCandidates>,
+    mut rng: ResMut<GameRng>,
+) {
+    candidates.refresh(&mut *rng);
+}
+
+pub fn hire_worker(
+    mut ev_hire: EventReader<HireWorkerEvent>,
+    mut candidates: ResMut<WorkerCandidates>,
+    mut money: ResMut<PlayerMoney>,
+) {
+    for ev in ev_hire.read() {
+        let Some(candidate) = candidates.candidates.get(ev.candidate) else { continue };
+        if let Err(err) = money.try_remove_money(candidate.get_hire_price()) {
+            println!("Can't hire {}: {}", candidate.profile.name, err);
+            continue;
+        }
+        if let Some(candidate) = candidates.take(ev.candidate) {
+            candidates.hired.push(candidate);
+        }
+    }
+}
+
+pub fn spawn_worker(
+    commands: &mut Commands,
+    sprites: &SpriteStorage,
+    pos: Vec2,
+    candidate: WorkerCandidate,
+) -> Entity {
+    let mut bundle = WorkerBundle::default_with_sprites(sprites);
+    bundle.sprite.transform.translation = Vec3::new(pos.x, pos.y, 5.0);
+    bundle.stats = candidate.stats;
+    bundle.profile = candidate.profile;
+    commands.spawn(bundle).id()
+}
+
+// New hires walk in at the same spot workers are sent back to every night
+pub fn place_hired_workers(
+    mut commands: Commands,
+    mut candidates: ResMut<WorkerCandidates>,
+    sprites: Res<SpriteStorage>,
+) {
+    for (i, candidate) in candidates.hired.drain(..).enumerate() {
+        let pos = Vec2::new(i as f32 * TILE_SIZE.x, 0.0);
+        spawn_worker(&mut commands, &sprites, pos, candidate);
+    }
+}
+
+pub fn fire_worker(
+    mut commands: Commands,
+    mut ev_fire: EventReader<FireWorkerEvent>,
+    q_workers: Query<(&GlobalTransform, &ItemContainer), With<Worker>>,
+    mut q_items: Query<&mut Transform, With<Item>>,
+    mut selected_worker: ResMut<SelectedWorker>,
+    mut next_state: ResMut<NextState<PlayerState>>,
+    mut ev_pick_up: EventReader<WorkerPickUpItemEvent>,
+    mut locked_items: ResMut<ItemJobLock>,
+) {
+    let pick_ups = ev_pick_up.read().map(|ev| (ev.worker, ev.item)).collect::<Vec<_>>();
+    for ev in ev_fire.read() {
+        let Ok((worker_transform, container)) = q_workers.get(ev.worker) else { continue };
+        // Nobody else could pick up items still locked for this worker
+        let in_flight = pick_ups.iter()
+            .filter(|(worker, _)| *worker == ev.worker)
+            .map(|(_, item)| *item)
+            .chain(container.items.iter().filter_map(|item| *item))
+            .collect::<Vec<_>>();
+        locked_items.items.retain(|item| !in_flight.contains(item));
+
+        // Anything they were carrying is left on the floor where they stood
+        for item in container.items.iter().filter_map(|item| *item) {
+            let Ok(mut item_transform) = q_items.get_mut(item) else { continue };
+            item_transform.translation.x = worker_transform.translation().x;
+            item_transform.translation.y = worker_transform.translation().y;
+            commands.entity(ev.worker).remove_children(&[item]);
+        }
+        commands.entity(ev.worker).despawn_recursive();
+
+        if selected_worker.selected == Some(ev.worker) {
+            selected_worker.selected = None;
+            next_state.set(PlayerState::None);
+        }
+    }
+}
+
+pub fn worker_gain_experience(
+    mut q_workers: Query<(&Job, &mut WorkerExperience)>,
+) {
+    for (job, mut experience) in q_workers.iter_mut() {
+        for job_point in job.path.iter() {
+            let rewarded = experience.completed.contains(&job_point.id);
+            if job_point.job_status == JobStatus::Completed && !rewarded {
+                experience.xp += XP_PER_JOB_POINT;
+                experience.completed.push(job_point.id);
+            } else if job_point.job_status == JobStatus::Active && rewarded {
+                experience.completed.retain(|id| *id != job_point.id);
+            }
+        }
+    }
+}
+
+pub fn worker_fatigue(
+    time: Res<Time>,
+    mut q_workers: Query<(&WorkerState, &Job, &WorkerStats, &mut WorkerFatigue)>,
+) {
+    for (state, job, stats, mut fatigue) in q_workers.iter_mut() {
+        if *state == WorkerState::Paused || job.path.is_empty() {
+            continue;
+        }
+        fatigue.0 = (fatigue.0 + FATIGUE_PER_SECOND * stats.fatigue_rate * time.delta_seconds()).min(1.0);
+    }
+}
+
+pub fn apply_worker_stats(
+    mut q_workers: Query<(&WorkerStats, &WorkerExperience, &WorkerFatigue, &mut PowerProduction, &mut Movement, &mut ItemContainer), With<Worker>>,
+) {
+    for (stats, experience, fatigue, mut production, mut movement, mut items) in q_workers.iter_mut() {
+        let level_mult = 1.0 + (experience.get_level() - 1) as f32 * LEVEL_BONUS;
+        let power = BASE_WORKER_POWER * stats.strength * level_mult * (1.0 - fatigue.0 * FATIGUE_POWER_PENALTY);
+        let speed = BASE_WORKER_SPEED * stats.speed * level_mult * (1.0 - fatigue.0 * FATIGUE_SPEED_PENALTY);
+
+        production.power = production.power.with_amount(power);
+        movement.speed_x = speed;
+        movement.speed_y = speed;
+        items.max_items = stats.carry_capacity;
+    }
+}
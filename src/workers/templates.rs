@@ -0,0 +1,357 @@
+use std::time::Duration;
+
+use crate::*;
+
+// Template steps point at the nearest building of a role instead of an entity,
+// so they still work after the factory layout is rebuilt
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum JobRole {
+    Assembly(AssemblyType),
+    Import,
+    Export,
+}
+impl JobRole {
+    pub fn from_components(
+        assembly_type: Option<&AssemblyType>,
+        import: Option<&ItemImport>,
+        export: Option<&ItemExport>,
+    ) -> Option<JobRole> {
+        if let Some(assembly_type) = assembly_type {
+            return Some(JobRole::Assembly(*assembly_type));
+        }
+        if import.is_some() {
+            return Some(JobRole::Import);
+        }
+        export.map(|_| JobRole::Export)
+    }
+
+    pub fn get_name(&self, definitions: &AssemblyDefinitions) -> String {
+        match self {
+            JobRole::Assembly(AssemblyType::Custom(id)) => definitions.get(*id)
+                .map_or(format!("Assembly {}", id), |definition| definition.name.clone()),
+            JobRole::Assembly(assembly_type) => format!("{:?}", assembly_type),
+            JobRole::Import => "Import".to_string(),
+            JobRole::Export => "Export".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TemplateAction {
+    Work {
+        role: JobRole
+    },
+    ContainerPickup {
+        role: JobRole,
+        pickup_amount: i32,
+        #[serde(default)]
+        filter: ItemFilter
+    },
+    Drop {
+        role: Option<JobRole>
+    },
+    WaitForItems {
+        role: JobRole,
+        amount: usize
+    },
+    Goto {
+        step: usize
+    },
+    StopIfFull {
+        role: JobRole
+    },
+    Zone {
+        zone: ZoneType
+    },
+    Idle
+}
+impl TemplateAction {
+    pub fn get_role(&self) -> Option<JobRole> {
+        match self {
+            TemplateAction::Work { role }
+            | TemplateAction::ContainerPickup { role, .. }
+            | TemplateAction::WaitForItems { role, .. }
+            | TemplateAction::StopIfFull { role } => Some(*role),
+            TemplateAction::Drop { role } => *role,
+            TemplateAction::Goto { .. } | TemplateAction::Zone { .. } | TemplateAction::Idle => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateStep {
+    pub action: TemplateAction,
+    pub condition: JobCondition,
+    // Only used by steps without a role, like idling on a tile
+    pub point: [u32; 2],
+    pub timer: Option<f32>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JobTemplate {
+    pub name: String,
+    pub steps: Vec<TemplateStep>
+}
+impl JobTemplate {
+    // Named after the buildings it visits, e.g. "Import > SawMill > Export"
+    pub fn get_default_name(&self, definitions: &AssemblyDefinitions) -> String {
+        let mut names: Vec<String> = Vec::new();
+        for role in self.steps.iter().filter_map(|step| step.action.get_role()) {
+            let name = role.get_name(definitions);
+            if names.last() != Some(&name) {
+                names.push(name);
+            }
+        }
+        if names.is_empty() {
+            return "Idle".to_string();
+        }
+        names.join(" > ")
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct JobTemplates {
+    pub templates: Vec<JobTemplate>,
+    pub selected: usize
+}
+impl JobTemplates {
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.templates.len() { return }
+        self.templates.remove(index);
+        self.selected = self.selected.min(self.templates.len().saturating_sub(1));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TemplateButton {
+    Save,
+    Update,
+    Apply,
+    ApplyAll,
+    Delete,
+}
+impl TemplateButton {
+    pub const ALL: [TemplateButton; 5] = [
+        TemplateButton::Save,
+        TemplateButton::Update,
+        TemplateButton::Apply,
+        TemplateButton::ApplyAll,
+        TemplateButton::Delete,
+    ];
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            TemplateButton::Save => "Save",
+            TemplateButton::Update => "Update",
+            TemplateButton::Apply => "Apply",
+            TemplateButton::ApplyAll => "All",
+            TemplateButton::Delete => "Delete",
+        }
+    }
+}
+
+// Saves the worker's job as a new template, or overwrites an existing one
+#[derive(Event)]
+pub struct SaveJobTemplateEvent {
+    pub worker: Entity,
+    pub template: Option<usize>
+}
+
+#[derive(Event)]
+pub struct ApplyJobTemplateEvent {
+    pub template: usize,
+    pub workers: Vec<Entity>
+}
+
+pub fn save_job_template(
+    mut ev_save: EventReader<SaveJobTemplateEvent>,
+    mut templates: ResMut<JobTemplates>,
+    definitions: Res<AssemblyDefinitions>,
+    mut q_jobs: Query<(&Job, &mut JobError)>,
+    q_roles: Query<(Option<&AssemblyType>, Option<&ItemImport>, Option<&ItemExport>)>,
+) {
+    for ev in ev_save.read() {
+        let Ok((job, mut job_error)) = q_jobs.get_mut(ev.worker) else { continue };
+        let get_role = |entity: Entity| q_roles.get(entity).ok()
+            .and_then(|(assembly_type, import, export)| JobRole::from_components(assembly_type, import, export));
+
+        let mut steps = Vec::new();
+        let mut kept = Vec::new();
+        for (i, job_point) in job.path.iter().enumerate() {
+            let action = match &job_point.action {
+                JobAction::Work { assembly } => {
+                    let Some(role) = get_role(*assembly) else { continue };
+                    TemplateAction::Work { role }
+                },
+                JobAction::ContainerPickup { container, pickup_amount, filter } => {
+                    let Some(role) = get_role(*container) else { continue };
+                    TemplateAction::ContainerPickup { role, pickup_amount: *pickup_amount, filter: filter.clone() }
+                },
+                JobAction::Drop { input_container, .. } => TemplateAction::Drop {
+                    role: input_container.and_then(get_role)
+                },
+                JobAction::WaitForItems { container, amount } => {
+                    let Some(role) = get_role(*container) else { continue };
+                    TemplateAction::WaitForItems { role, amount: *amount }
+                },
+                JobAction::Goto { step } => TemplateAction::Goto { step: *step },
+                JobAction::StopIfFull { container } => {
+                    let Some(role) = get_role(*container) else { continue };
+                    TemplateAction::StopIfFull { role }
+                },
+                JobAction::Zone { zone } => TemplateAction::Zone { zone: *zone },
+                JobAction::Idle => TemplateAction::Idle,
+                // Loose items only exist once so they can't be reused
+                JobAction::Pickup { .. } => continue,
+            };
+            steps.push(TemplateStep {
+                action,
+                condition: job_point.condition,
+                point: [job_point.point.x, job_point.point.y],
+                timer: job_point.timer.as_ref().map(|t| t.duration().as_secs_f32())
+            });
+            kept.push(i);
+        }
+        if steps.is_empty() {
+            job_error.set_error("Job has no steps to save as a template");
+            continue;
+        }
+        for step in steps.iter_mut() {
+            if let TemplateAction::Goto { step } = &mut step.action {
+                *step = get_kept_step(&kept, *step);
+            }
+        }
+
+        let mut template = JobTemplate { name: String::new(), steps };
+        template.name = template.get_default_name(&definitions);
+        let dropped = job.path.len() - kept.len();
+        if dropped > 0 {
+            job_error.set_warning(&format!("{} steps left out of template {}", dropped, template.name));
+        }
+        match ev.template.and_then(|i| templates.templates.get_mut(i)) {
+            Some(existing) => {
+                // Names typed in by the player are kept when the template is updated
+                if existing.name != existing.get_default_name(&definitions) {
+                    template.name = existing.name.clone();
+                }
+                *existing = template
+            },
+            None => {
+                templates.templates.push(template);
+                templates.selected = templates.templates.len() - 1;
+            }
+        }
+    }
+}
+
+pub fn apply_job_template(
+    mut ev_apply: EventReader<ApplyJobTemplateEvent>,
+    templates: Res<JobTemplates>,
+    mut q_workers: Query<(&Transform, &mut Job, &mut JobError), With<Worker>>,
+    q_targets: Query<(Entity, &Transform, Option<&EntityTileSize>, Option<&AssemblyType>, Option<&ItemImport>, Option<&ItemExport>, Option<&Children>), Without<Worker>>,
+    q_input_selectors: Query<&GlobalTransform, With<ContainerInputSelector>>,
+    q_output_selectors: Query<&GlobalTransform, With<ContainerOutputSelector>>,
+    q_tilemap: Query<(&TilemapSize, &TilemapGridSize, &Transform, &TilemapType), Without<Worker>>,
+    definitions: Res<AssemblyDefinitions>,
+) {
+    let Ok((tilemap_size, grid_size, map_transform, map_type)) = q_tilemap.get_single() else { return };
+    let to_tile_pos = |pos: Vec2| TilePos::from_world_pos(&get_world_pos(pos, map_transform), tilemap_size, grid_size, map_type);
+
+    for ev in ev_apply.read() {
+        let Some(template) = templates.templates.get(ev.template) else { continue };
+        for worker_entity in ev.workers.iter() {
+            let Ok((worker_transform, mut job, mut job_error)) = q_workers.get_mut(*worker_entity) else { continue };
+            let worker_pos = worker_transform.translation.xy();
+
+            let mut path = Vec::new();
+            let mut kept = Vec::new();
+            let mut missing = None;
+            for (i, step) in template.steps.iter().enumerate() {
+                let target = step.action.get_role().and_then(|role| q_targets.iter()
+                    .filter(|(_, _, _, assembly_type, import, export, _)| {
+                        JobRole::from_components(*assembly_type, *import, *export) == Some(role)
+                    })
+                    .min_by(|a, b| {
+                        a.1.translation.xy().distance_squared(worker_pos)
+                            .total_cmp(&b.1.translation.xy().distance_squared(worker_pos))
+                    })
+                );
+                if let (Some(role), None) = (step.action.get_role(), target) {
+                    missing = Some(role);
+                    break;
+                }
+
+                let input_pos = target.and_then(|(_, _, _, _, _, _, children)| children?.iter()
+                    .find_map(|child| q_input_selectors.get(*child).ok())
+                    .map(|transform| transform.translation().xy()));
+                let output_pos = target.and_then(|(_, _, _, _, _, _, children)| children?.iter()
+                    .find_map(|child| q_output_selectors.get(*child).ok())
+                    .map(|transform| transform.translation().xy()));
+                let target_entity = target.map(|(entity, ..)| entity);
+                let default_point = TilePos::new(step.point[0], step.point[1]);
+
+                let (action, point, point_size) = match &step.action {
+                    TemplateAction::Work { .. } => {
+                        let Some((assembly, transform, Some(tile_size), ..)) = target else { continue };
+                        let assembly_pos = get_corner_tile_pos(get_world_pos(transform.translation.xy(), map_transform), tile_size.0);
+                        let Some(point) = TilePos::from_world_pos(&assembly_pos, tilemap_size, grid_size, map_type) else { continue };
+                        (JobAction::Work { assembly }, point, tile_size.0)
+                    },
+                    TemplateAction::ContainerPickup { pickup_amount, filter, .. } => (
+                        JobAction::ContainerPickup { container: target_entity.unwrap(), pickup_amount: *pickup_amount, filter: filter.clone() },
+                        output_pos.and_then(to_tile_pos).unwrap_or(default_point),
+                        IVec2::ONE
+                    ),
+                    TemplateAction::WaitForItems { amount, .. } => (
+                        JobAction::WaitForItems { container: target_entity.unwrap(), amount: *amount },
+                        output_pos.and_then(to_tile_pos).unwrap_or(default_point),
+                        IVec2::ONE
+                    ),
+                    TemplateAction::Drop { .. } => (
+                        JobAction::Drop { worker: *worker_entity, input_container: target_entity },
+                        input_pos.and_then(to_tile_pos).unwrap_or(default_point),
+                        IVec2::ONE
+                    ),
+                    TemplateAction::StopIfFull { .. } => (
+                        JobAction::StopIfFull { container: target_entity.unwrap() },
+                        input_pos.and_then(to_tile_pos).unwrap_or(default_point),
+                        IVec2::ONE
+                    ),
+                    TemplateAction::Goto { step } => (JobAction::Goto { step: *step }, default_point, IVec2::ONE),
+                    // The stored tile is moved onto the zone by worker_iterate_jobs if it was repainted
+                    TemplateAction::Zone { zone } => (JobAction::Zone { zone: *zone }, default_point, IVec2::ONE),
+                    TemplateAction::Idle => (JobAction::Idle, default_point, IVec2::ONE),
+                };
+                path.push(JobPoint {
+                    id: i as u32,
+                    point,
+                    point_size,
+                    job_status: JobStatus::Active,
+                    action,
+                    condition: step.condition,
+                    timer: step.timer.map(|t| Timer::new(Duration::from_secs_f32(t), TimerMode::Once))
+                });
+                kept.push(i);
+            }
+
+            // The worker keeps their old job rather than getting half a template
+            if let Some(role) = missing {
+                job_error.set_error(&format!("No {} for template {}", role.get_name(&definitions), template.name));
+                continue;
+            }
+            for job_point in path.iter_mut() {
+                if let JobAction::Goto { step } = &mut job_point.action {
+                    *step = get_kept_step(&kept, *step);
+                }
+            }
+            let dropped = template.steps.len() - kept.len();
+            job.path = path;
+            job.current_job = None;
+            job.insert_at = None;
+            job_error.clear_error();
+            if dropped > 0 {
+                job_error.set_warning(&format!("{} steps of template {} had no building to use", dropped, template.name));
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::*;
 
-// TODO: Dynamic prices? Skill?
+// Hiring price for a worker on the base wage
 pub const WORKER_PRICE: f32 = 5.0;
 
 #[derive(Component, Debug, Reflect)]
@@ -13,7 +13,7 @@ pub struct PowerProduction {
 pub struct Worker;
 impl Clickable for Worker {}
 
-#[derive(Component, PartialEq, Debug, Reflect)]
+#[derive(Component, PartialEq, Debug, Reflect, Clone, Copy, Serialize, Deserialize)]
 pub enum WorkerState {
     Paused,
     Working
@@ -31,7 +31,11 @@ pub struct WorkerBundle {
     pub movement: Movement,
     pub direction: SpriteDirection,
     pub pathfinding: MoveToTile,
-    pub production: PowerProduction
+    pub production: PowerProduction,
+    pub stats: WorkerStats,
+    pub profile: WorkerProfile,
+    pub experience: WorkerExperience,
+    pub fatigue: WorkerFatigue
 }
 impl GetGhostBundle for WorkerBundle {
     fn get_spritesheet_bundle(&self) -> Option<SpriteSheetBundle> {
@@ -50,7 +54,9 @@ impl DefaultWithSprites for WorkerBundle {
                 path: Vec::new(),
                 complexity: 0.0,
                 current_job: None,
-                lock: false
+                lock: false,
+                step_items: 0,
+                insert_at: None
             },
             job_error: JobError::new(),
             job_waiting: JobWaiting(false),
@@ -70,7 +76,11 @@ impl DefaultWithSprites for WorkerBundle {
             },
             direction: SpriteDirection::default(),
             movement: Movement { speed_x: 1.25, speed_y: 1.25, input: None },
-            pathfinding: MoveToTile { target: None, path: None, path_i: 0 }
+            pathfinding: MoveToTile { target: None, path: None, path_i: 0, blocked_time: 0.0, wait_time: 0.0, side_stepped: false },
+            stats: WorkerStats::default(),
+            profile: WorkerProfile::default(),
+            experience: WorkerExperience::default(),
+            fatigue: WorkerFatigue::default()
         }
     }
 }
@@ -87,10 +97,12 @@ pub fn place_worker(
         &Transform
     )>,
     mut money: ResMut<PlayerMoney>,
+    mut candidates: ResMut<WorkerCandidates>,
     sprites: Res<SpriteStorage>,
 ) {
     if input.just_pressed(MouseButton::Left) {
-        let Ok(_) = money.try_remove_money(WORKER_PRICE) else { 
+        let Some(candidate) = candidates.get_selected() else { return };
+        let Ok(_) = money.try_remove_money(candidate.get_hire_price()) else { 
             println!("Can't afford worker"); 
             return
         };
@@ -102,16 +114,9 @@ pub fn place_worker(
         let Some(tile_pos) = get_mouse_tile(window, camera, camera_transform, tilemap_size, grid_size, map_type, map_transform) else { return };
         let pos = get_tile_world_pos(&tile_pos, map_transform, grid_size, map_type);
 
-        commands.spawn(WorkerBundle {
-            sprite: SpriteSheetBundle {
-                transform: Transform {
-                    translation: Vec3::new(pos.x, pos.y, 5.0),
-                    ..WorkerBundle::default_with_sprites(&sprites).sprite.transform
-                },
-                ..WorkerBundle::default_with_sprites(&sprites).sprite
-            },
-            ..WorkerBundle::default_with_sprites(&sprites)
-        });
+        let selected = candidates.selected;
+        let Some(candidate) = candidates.take(selected) else { return };
+        spawn_worker(&mut commands, &sprites, pos, candidate);
     }
 }
 
@@ -267,21 +272,37 @@ pub fn worker_pick_up_item(
         *item_transform = worker_container.get_transform();
         println!("Picked up item {:?}", worker_container);
         
+        let mut source_remaining = 0;
         if let Some(container_entity) = ev.container {
             if let Ok(mut container) = q_item_containers.get_mut(container_entity) {
                 if let Err(err) = container.remove_item(Some(ev.item)) {}
+                source_remaining = container.items.len();
             } else if let Ok(mut container) = q_io_item_containers.get_mut(container_entity) {
                 if let Err(err) = container.output.remove_item(Some(ev.item)) {}
+                source_remaining = container.output.items.len();
             }
         }
         if let Some(current_job_i) = job.current_job {
+            job.step_items += 1;
+            let step_items = job.step_items;
             let Some(current_job) = job.path.get_mut(current_job_i) else {
                 if let Some(pos) = locked_items.items.iter().position(|x| *x == ev.item) {
                     locked_items.items.remove(pos);
                 }
                 continue;
             };
-            current_job.job_status = JobStatus::Completed;
+            let pickup_amount = match current_job.action {
+                JobAction::ContainerPickup { pickup_amount, .. } => pickup_amount.max(1) as usize,
+                _ => 1,
+            };
+            // Stay on the step while there is more to take and room to carry it
+            let repeat = (current_job.condition == JobCondition::RepeatUntilFull || step_items < pickup_amount)
+                && source_remaining > 0
+                && worker_container.items.len() < worker_container.max_items;
+            if !repeat {
+                current_job.job_status = JobStatus::Completed;
+                job.step_items = 0;
+            }
             job_error.clear_error();
         }
         if let Some(pos) = locked_items.items.iter().position(|x| *x == ev.item) {
@@ -338,13 +359,16 @@ pub fn worker_drop_item(
                 if let Err(err) = worker_container.add_item((Some(ev.item), Some(*item_type))) {
                     job_error.set_error(format!("Error picking item back up: {err}").as_str());
                 }
-            } else {
+            } else if worker_container.items.is_empty() || container.items.len() >= container.max_items {
+                // Keep unloading until the worker is empty or the container can't take any more
                 if let Some(current_job_i) = job.current_job {
                     if let Some(current_job) = job.path.get_mut(current_job_i) {
                         current_job.job_status = JobStatus::Completed;
                         job_error.clear_error();
                     }
                 }
+            } else {
+                job_error.clear_error();
             }
         };
 